@@ -0,0 +1,124 @@
+//! Compares extracting annotations of a large contract by cloning the annotation value,
+//! as `TryFromDebugInfo` used to, with deserializing it in place.
+//!
+//! Run with `cargo bench --bench annotations`.
+
+use cairo_annotations::annotations::TryFromDebugInfo;
+use cairo_annotations::annotations::coverage::{
+    CoverageAnnotationsV1, VersionedCoverageAnnotations,
+};
+use cairo_lang_sierra::debug_info::DebugInfo;
+use serde_json::{Value, json};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Number of sierra statements, comparable with a large contract.
+const STATEMENTS: usize = 200_000;
+/// Number of code locations of each statement.
+const LOCATIONS_PER_STATEMENT: usize = 3;
+const ITERATIONS: u32 = 5;
+
+/// Allocator keeping track of the peak number of allocated bytes.
+struct PeakAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(allocated, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) };
+    }
+}
+
+#[global_allocator]
+static GLOBAL: PeakAllocator = PeakAllocator;
+
+fn main() {
+    let debug_info = large_debug_info();
+    let namespace = "github.com/software-mansion/cairo-coverage";
+
+    let cloned = measure(|| {
+        let value = debug_info.annotations.get(namespace).unwrap();
+        serde_json::from_value::<CoverageAnnotationsV1>(value.clone())
+            .map(VersionedCoverageAnnotations::V1)
+            .unwrap()
+    });
+    let borrowed =
+        measure(|| VersionedCoverageAnnotations::try_from_debug_info(&debug_info).unwrap());
+
+    println!("{STATEMENTS} statements with {LOCATIONS_PER_STATEMENT} code locations each:");
+    println!("  cloned:   {cloned}");
+    println!("  borrowed: {borrowed}");
+}
+
+struct Measurement {
+    time: Duration,
+    peak_bytes: usize,
+}
+
+impl std::fmt::Display for Measurement {
+    // Precision loss is irrelevant for a report.
+    #[allow(clippy::cast_precision_loss)]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:>10.2?} per iteration, {:>8.1} MiB peak memory",
+            self.time,
+            self.peak_bytes as f64 / (1024.0 * 1024.0)
+        )
+    }
+}
+
+fn measure<T>(extract: impl Fn() -> T) -> Measurement {
+    let baseline = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    drop(black_box(extract()));
+    let peak_bytes = PEAK.load(Ordering::Relaxed) - baseline;
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        drop(black_box(extract()));
+    }
+    Measurement {
+        time: start.elapsed() / ITERATIONS,
+        peak_bytes,
+    }
+}
+
+fn large_debug_info() -> DebugInfo {
+    let location = |statement: usize, depth: usize| {
+        let position = json!({ "line": statement / 10, "col": depth * 4 });
+        json!([
+            format!("/path/to/project/src/module_{}.cairo", statement % 50),
+            { "start": position, "end": position },
+            depth > 0,
+        ])
+    };
+    let statements_code_locations: serde_json::Map<String, Value> = (0..STATEMENTS)
+        .map(|statement| {
+            let locations = (0..LOCATIONS_PER_STATEMENT)
+                .map(|depth| location(statement, depth))
+                .collect();
+            (statement.to_string(), Value::Array(locations))
+        })
+        .collect();
+
+    DebugInfo {
+        annotations: [(
+            "github.com/software-mansion/cairo-coverage".to_string(),
+            json!({ "statements_code_locations": statements_code_locations }),
+        )]
+        .into_iter()
+        .collect(),
+        ..Default::default()
+    }
+}
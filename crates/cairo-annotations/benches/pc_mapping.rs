@@ -0,0 +1,88 @@
+//! Compares mapping a long trace with binary search over the statements, as done by
+//! `map_pcs_to_sierra_statement_ids`, with the lookup table of `PcMapper`.
+//!
+//! Run with `cargo bench --bench pc_mapping`, optionally with `--features rayon`.
+
+use cairo_annotations::trace_data::{CasmLevelInfo, TraceEntry};
+use cairo_annotations::{PcMapper, map_pcs_to_sierra_statement_ids};
+use cairo_lang_sierra_to_casm::compiler::{
+    CairoProgramDebugInfo, ReturnStatementDebugInfo, SierraStatementDebugInfo,
+    StatementKindDebugInfo,
+};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Number of sierra statements, comparable with a large program.
+const STATEMENTS: usize = 200_000;
+/// Number of trace entries, comparable with a long test.
+const TRACE_LEN: usize = 5_000_000;
+const ITERATIONS: u32 = 5;
+
+fn main() {
+    let debug_info = debug_info();
+    let casm_level_info = casm_level_info(&debug_info);
+
+    let binary_search = measure(|| map_pcs_to_sierra_statement_ids(&debug_info, &casm_level_info));
+    let build = measure(|| PcMapper::for_trace(&debug_info, &casm_level_info));
+    let pc_mapper = PcMapper::for_trace(&debug_info, &casm_level_info);
+    let lookup = measure(|| pc_mapper.map_trace(&casm_level_info.vm_trace));
+
+    assert_eq!(
+        pc_mapper.map_trace(&casm_level_info.vm_trace),
+        map_pcs_to_sierra_statement_ids(&debug_info, &casm_level_info)
+    );
+
+    println!("{TRACE_LEN} trace entries, {STATEMENTS} statements:");
+    println!("  binary search:          {binary_search:>10.2?}");
+    println!("  lookup table building:  {build:>10.2?}");
+    println!("  lookup table mapping:   {lookup:>10.2?}");
+}
+
+fn measure<T>(run: impl Fn() -> T) -> Duration {
+    drop(black_box(run()));
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        drop(black_box(run()));
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn debug_info() -> CairoProgramDebugInfo {
+    let sierra_statement_info = (0..STATEMENTS)
+        .map(|statement| {
+            // Statements of 2 or 3 felts.
+            let offset = |statement: usize| statement * 5 / 2;
+            SierraStatementDebugInfo {
+                start_offset: offset(statement),
+                end_offset: offset(statement + 1),
+                instruction_idx: statement,
+                additional_kind_info: StatementKindDebugInfo::Return(ReturnStatementDebugInfo {
+                    ref_values: Vec::new(),
+                }),
+            }
+        })
+        .collect();
+    CairoProgramDebugInfo {
+        sierra_statement_info,
+    }
+}
+
+fn casm_level_info(debug_info: &CairoProgramDebugInfo) -> CasmLevelInfo {
+    let bytecode_length = debug_info.sierra_statement_info.last().unwrap().end_offset;
+    // Deterministic pseudo-random pcs, including some in the header and out of the function area.
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let vm_trace = (0..TRACE_LEN)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let pc = usize::try_from(state % (bytecode_length as u64 + 20)).unwrap();
+            TraceEntry { pc, ap: 0, fp: 0 }
+        })
+        .collect();
+    CasmLevelInfo {
+        run_with_call_header: false,
+        vm_trace,
+        program_offset: Some(10),
+    }
+}
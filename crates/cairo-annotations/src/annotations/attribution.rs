@@ -0,0 +1,175 @@
+use crate::annotations::coverage::CodeLocation;
+use crate::source_code::scarb_cache_relative_path;
+
+/// Policy deciding which frames of an inlined stack a sierra statement is attributed to.
+///
+/// Both [`CoverageAnnotationsV1`](crate::annotations::coverage::CoverageAnnotationsV1) and
+/// [`ProfilerAnnotationsV1`](crate::annotations::profiler::ProfilerAnnotationsV1) map a statement
+/// to a stack of frames, starting from the least meaningful one, i.e. the innermost inlined function,
+/// up to the first non-inlined function from the original code.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum AttributionPolicy {
+    /// The innermost frame - the code which directly generated the statement, e.g. a body of an inlined function.
+    Innermost,
+    /// The outermost frame which is not located in a dependency from the Scarb package cache.
+    /// Falls back to the outermost frame if all of them are located in dependencies.
+    OutermostUserCode,
+    /// All frames of the stack.
+    #[default]
+    AllFrames,
+    /// The innermost frame which was not generated by a macro.
+    /// Falls back to the outermost frame if all of them were generated by macros.
+    FirstNonMacro,
+}
+
+impl AttributionPolicy {
+    /// Returns indices of the frames in `code_locations` that a statement is attributed to.
+    ///
+    /// Returns an empty vector only if `code_locations` is empty.
+    #[must_use]
+    pub fn select_frames(self, code_locations: &[CodeLocation]) -> Vec<usize> {
+        let Some(outermost) = code_locations.len().checked_sub(1) else {
+            return Vec::new();
+        };
+
+        match self {
+            AttributionPolicy::Innermost => vec![0],
+            AttributionPolicy::AllFrames => (0..code_locations.len()).collect(),
+            AttributionPolicy::OutermostUserCode => vec![
+                code_locations
+                    .iter()
+                    .rposition(|CodeLocation(path, _, _)| {
+                        scarb_cache_relative_path(&path.0).is_none()
+                    })
+                    .unwrap_or(outermost),
+            ],
+            AttributionPolicy::FirstNonMacro => vec![
+                code_locations
+                    .iter()
+                    .position(|CodeLocation(_, _, macro_flag)| *macro_flag != Some(true))
+                    .unwrap_or(outermost),
+            ],
+        }
+    }
+
+    /// Selects the frames of a stack for which only its length is known, e.g. a stack of function names
+    /// without corresponding code locations.
+    ///
+    /// Since there is no information about paths and macros, [`AttributionPolicy::OutermostUserCode`]
+    /// selects the outermost frame and [`AttributionPolicy::FirstNonMacro`] selects the innermost frame.
+    #[must_use]
+    pub fn select_frames_by_position(self, stack_len: usize) -> Vec<usize> {
+        let Some(outermost) = stack_len.checked_sub(1) else {
+            return Vec::new();
+        };
+
+        match self {
+            AttributionPolicy::Innermost | AttributionPolicy::FirstNonMacro => vec![0],
+            AttributionPolicy::AllFrames => (0..stack_len).collect(),
+            AttributionPolicy::OutermostUserCode => vec![outermost],
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::annotations::profiler::{FunctionName, ProfilerAnnotationsV1};
+    use crate::coverage_report::test::{annotations, location};
+    use cairo_lang_sierra::program::StatementIdx;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_select_frames() {
+        let dependency = "/home/user/.cache/scarb/registry/std/v2.11.0/core/src/lib.cairo";
+        let code_locations = [
+            location(dependency, 1, Some(false)),
+            location("/project/src/lib.cairo[assert_macro]", 5, Some(true)),
+            location("/project/src/lib.cairo", 5, Some(false)),
+            location(dependency, 7, Some(false)),
+        ];
+
+        assert_eq!(
+            AttributionPolicy::Innermost.select_frames(&code_locations),
+            vec![0]
+        );
+        assert_eq!(
+            AttributionPolicy::AllFrames.select_frames(&code_locations),
+            vec![0, 1, 2, 3]
+        );
+        assert_eq!(
+            AttributionPolicy::OutermostUserCode.select_frames(&code_locations),
+            vec![2]
+        );
+        assert_eq!(
+            AttributionPolicy::FirstNonMacro.select_frames(&code_locations[1..]),
+            vec![1]
+        );
+        assert_eq!(
+            AttributionPolicy::FirstNonMacro.select_frames(&code_locations[1..2]),
+            vec![0]
+        );
+        assert_eq!(
+            AttributionPolicy::OutermostUserCode.select_frames(&[]),
+            Vec::<usize>::new()
+        );
+    }
+
+    #[test]
+    fn test_select_frames_by_position() {
+        assert_eq!(
+            AttributionPolicy::OutermostUserCode.select_frames_by_position(3),
+            vec![2]
+        );
+        assert_eq!(
+            AttributionPolicy::FirstNonMacro.select_frames_by_position(3),
+            vec![0]
+        );
+        assert_eq!(
+            AttributionPolicy::AllFrames.select_frames_by_position(0),
+            Vec::<usize>::new()
+        );
+    }
+
+    #[test]
+    fn test_coverage_and_profiler_agree() {
+        let coverage_annotations = annotations([(
+            0,
+            vec![
+                location("/project/src/lib.cairo[assert_macro]", 5, Some(true)),
+                location("/project/src/lib.cairo", 5, Some(false)),
+                location("/project/src/lib.cairo", 9, Some(false)),
+            ],
+        )]);
+        let profiler_annotations = ProfilerAnnotationsV1 {
+            statements_functions: HashMap::from([(
+                StatementIdx(0),
+                ["assert", "lib::helper", "lib::main"]
+                    .map(|name| FunctionName(name.to_string()))
+                    .to_vec(),
+            )]),
+        };
+        let policy = AttributionPolicy::FirstNonMacro;
+
+        assert_eq!(
+            coverage_annotations.resolve_locations(StatementIdx(0), policy),
+            vec![&location("/project/src/lib.cairo", 5, Some(false))]
+        );
+        assert_eq!(
+            profiler_annotations.resolve_functions(
+                StatementIdx(0),
+                policy,
+                Some(&coverage_annotations)
+            ),
+            vec![&FunctionName("lib::helper".to_string())]
+        );
+        assert_eq!(
+            profiler_annotations.resolve_functions(StatementIdx(0), policy, None),
+            vec![&FunctionName("assert".to_string())]
+        );
+        assert_eq!(
+            profiler_annotations.resolve_functions(StatementIdx(1), policy, None),
+            Vec::<&FunctionName>::new()
+        );
+    }
+}
@@ -1,8 +1,13 @@
-use crate::annotations::impl_helpers::impl_namespace;
+use crate::annotations::attribution::AttributionPolicy;
+use crate::annotations::impl_helpers::impl_versioned_namespace;
 use cairo_lang_sierra::program::StatementIdx;
 use derive_more::{Add, AddAssign, Display, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use schemars::{JsonSchema, Schema};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
+use std::fmt;
+use thiserror::Error;
 
 /// Versioned representation of Coverage Annotations.
 ///
@@ -24,11 +29,33 @@ pub enum VersionedCoverageAnnotations {
 ///
 /// Needs `unstable-add-statements-code-locations-debug-info = true`
 /// under `[profile.dev.cairo]` in the Scarb config to be generated.
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct CoverageAnnotationsV1 {
+    #[schemars(with = "HashMap<usize, Vec<CodeLocation>>")]
     pub statements_code_locations: HashMap<StatementIdx, Vec<CodeLocation>>,
 }
 
+impl CoverageAnnotationsV1 {
+    /// Returns the code locations the statement is attributed to under `policy`.
+    ///
+    /// Returns an empty vector if the statement has no code locations.
+    #[must_use]
+    pub fn resolve_locations(
+        &self,
+        statement_idx: StatementIdx,
+        policy: AttributionPolicy,
+    ) -> Vec<&CodeLocation> {
+        let Some(code_locations) = self.statements_code_locations.get(&statement_idx) else {
+            return Vec::new();
+        };
+        policy
+            .select_frames(code_locations)
+            .into_iter()
+            .map(|frame| &code_locations[frame])
+            .collect()
+    }
+}
+
 /// Represents the location of a Sierra statement in the source code, along with an optional flag
 /// indicating whether it was generated by a macro.
 ///
@@ -36,20 +63,38 @@ pub struct CoverageAnnotationsV1 {
 /// - If `Some(true)`, the statement was generated by a macro.
 /// - If `Some(false)`, the statement was not generated by a macro.
 /// - If `None`, the information is unavailable (due to using an older `scarb` version).
-#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, JsonSchema)]
+#[schemars(transform = optional_macro_flag)]
 pub struct CodeLocation(
     pub SourceFileFullPath,
     pub SourceCodeSpan,
     #[serde(default, skip_serializing_if = "Option::is_none")] pub Option<bool>,
 );
 
+/// Allows omitting the macro-generated flag of [`CodeLocation`] in its schema, as `scarb` versions before `2.11.0`
+/// emit two-element locations.
+fn optional_macro_flag(schema: &mut Schema) {
+    schema.insert("minItems".to_string(), Value::from(2));
+}
+
 /// A full path to a Cairo source file.
 ///
 /// Up to `scarb` `2.8.5` will contain multiple Cairo virtual file markings if the code is generated by macros.
 /// Like `/path/to/project/lib.cairo[array_inline_macro][assert_macro]`
 /// where `array_inline_macro` and `assert_macro` is a virtual file marking.
 #[derive(
-    Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize, Display, Default,
+    Clone,
+    Debug,
+    Eq,
+    PartialEq,
+    Hash,
+    Ord,
+    PartialOrd,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    Display,
+    Default,
 )]
 pub struct SourceFileFullPath(pub String);
 
@@ -64,8 +109,8 @@ impl SourceFileFullPath {
     /// Note that this function returns `&str` instead of creating a new `SourceFileFullPath`
     /// to avoid unnecessary allocations.
     ///
-    /// # Panics
-    /// - If a virtual file marking does not end with `]`, which is unexpected.
+    /// If the markings are malformed, the whole path is returned with no markings.
+    /// Use [`SourceFileFullPath::parse_virtual_file_path`] to handle such paths explicitly.
     ///
     /// # Example
     /// ```
@@ -78,25 +123,125 @@ impl SourceFileFullPath {
     /// ```
     #[must_use]
     pub fn remove_virtual_file_markings(&self) -> (&str, Vec<&str>) {
-        let mut parts = self.0.split('[');
-        let path = parts
+        self.parse_virtual_file_path().map_or_else(
+            |_| (self.0.as_str(), Vec::new()),
+            |VirtualFilePath {
+                 real_path,
+                 markings,
+             }| (real_path, markings),
+        )
+    }
+
+    /// Splits the path into the real file path and the virtual file markings appended to it.
+    ///
+    /// Markings are only recognised at the end of the path, so brackets that are part of
+    /// directory names (e.g. `/projects/[old]/src/lib.cairo`) are kept in the real path.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VirtualFilePathError`] if the markings at the end of the path are malformed.
+    ///
+    /// # Example
+    /// ```
+    /// use cairo_annotations::annotations::coverage::SourceFileFullPath;
+    ///
+    /// let path = SourceFileFullPath("/projects/[old]/lib.cairo[assert_macro]".to_string());
+    /// let virtual_file_path = path.parse_virtual_file_path().unwrap();
+    /// assert_eq!(virtual_file_path.real_path, "/projects/[old]/lib.cairo");
+    /// assert_eq!(virtual_file_path.markings, vec!["assert_macro"]);
+    /// assert_eq!(virtual_file_path.to_string(), path.0);
+    /// ```
+    pub fn parse_virtual_file_path(&self) -> Result<VirtualFilePath<'_>, VirtualFilePathError> {
+        VirtualFilePath::parse(&self.0)
+    }
+}
+
+/// A [`SourceFileFullPath`] split into the path of the real file and virtual file markings.
+///
+/// Displaying it renders the original path back, without any loss.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct VirtualFilePath<'a> {
+    /// Path to the file that exists on disk.
+    pub real_path: &'a str,
+    /// Virtual file markings, in the order they appear in the path, without the brackets.
+    pub markings: Vec<&'a str>,
+}
+
+/// Error that can occur when parsing virtual file markings of a [`SourceFileFullPath`].
+#[derive(Clone, Debug, Eq, PartialEq, Error)]
+pub enum VirtualFilePathError {
+    /// Error indicating that a marking is closed with `]` but never opened with `[`.
+    #[error("Virtual file marking is missing an opening '[' in path: {0}")]
+    UnopenedMarking(String),
+
+    /// Error indicating that a marking is opened with `[` but never closed with `]`.
+    #[error("Virtual file marking is missing a closing ']' in path: {0}")]
+    UnclosedMarking(String),
+
+    /// Error indicating that a marking has no content, i.e. `[]`.
+    #[error("Empty virtual file marking in path: {0}")]
+    EmptyMarking(String),
+}
+
+impl<'a> VirtualFilePath<'a> {
+    fn parse(path: &'a str) -> Result<Self, VirtualFilePathError> {
+        let mut real_path = path;
+        let mut markings = Vec::new();
+
+        // Markings are appended at the very end of the path, so they are stripped from the back.
+        // A marking never contains a path separator or nested brackets, which lets us tell it
+        // apart from brackets used in directory names.
+        while let Some(rest) = real_path.strip_suffix(']') {
+            let opening = rest
+                .rfind(['[', ']', '/', '\\'])
+                .filter(|&index| rest[index..].starts_with('['))
+                .ok_or_else(|| VirtualFilePathError::UnopenedMarking(path.to_string()))?;
+
+            // A bracketed name that spans the whole file name is not a marking.
+            if rest[..opening].is_empty() || rest[..opening].ends_with(['/', '\\']) {
+                break;
+            }
+
+            let marking = &rest[opening + 1..];
+            if marking.is_empty() {
+                return Err(VirtualFilePathError::EmptyMarking(path.to_string()));
+            }
+
+            markings.push(marking);
+            real_path = &rest[..opening];
+        }
+        markings.reverse();
+
+        let file_name = real_path
+            .rsplit(['/', '\\'])
             .next()
             .unwrap_or_else(|| unreachable!("split always returns at least one element"));
+        if file_name
+            .rfind('[')
+            .is_some_and(|opening| !file_name[opening..].contains(']'))
+        {
+            return Err(VirtualFilePathError::UnclosedMarking(path.to_string()));
+        }
 
-        let virtual_file_markings = parts
-            .map(|virtual_file| {
-                virtual_file
-                    .strip_suffix(']')
-                    .expect("virtual file marking should end with ']'")
-            })
-            .collect();
+        Ok(Self {
+            real_path,
+            markings,
+        })
+    }
+}
 
-        (path, virtual_file_markings)
+impl fmt::Display for VirtualFilePath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.real_path)?;
+        for marking in &self.markings {
+            write!(f, "[{marking}]")?;
+        }
+        Ok(())
     }
 }
 
 /// A span in a Cairo source file.
-#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct SourceCodeSpan {
     /// Beginning of the text span in the Cairo source file.
     pub start: SourceCodeLocation,
@@ -105,7 +250,7 @@ pub struct SourceCodeSpan {
 }
 
 /// A location in a Cairo source file.
-#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct SourceCodeLocation {
     /// Line index, 0-based.
     /// Most editors show line numbers starting from 1, so when displaying to users, add 1 to this value.
@@ -126,6 +271,7 @@ pub struct SourceCodeLocation {
     PartialOrd,
     Serialize,
     Deserialize,
+    JsonSchema,
     Add,
     AddAssign,
     Sub,
@@ -150,6 +296,7 @@ pub struct ColumnNumber(pub usize);
     PartialOrd,
     Serialize,
     Deserialize,
+    JsonSchema,
     Add,
     AddAssign,
     Sub,
@@ -163,31 +310,11 @@ pub struct ColumnNumber(pub usize);
 )]
 pub struct LineNumber(pub usize);
 
-// We can't use untagged enum here. See https://github.com/serde-rs/json/issues/1103
-impl Serialize for VersionedCoverageAnnotations {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        match self {
-            VersionedCoverageAnnotations::V1(v1) => v1.serialize(serializer),
-        }
-    }
-}
-
-impl<'de> Deserialize<'de> for VersionedCoverageAnnotations {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        CoverageAnnotationsV1::deserialize(deserializer).map(VersionedCoverageAnnotations::V1)
-    }
-}
-
-impl_namespace!(
+impl_versioned_namespace!(
     "github.com/software-mansion/cairo-coverage",
+    VersionedCoverageAnnotations,
     CoverageAnnotationsV1,
-    VersionedCoverageAnnotations
+    "Coverage Annotations"
 );
 
 #[cfg(test)]
@@ -229,4 +356,70 @@ mod test {
         assert_eq!(path, "/path/to/project/lib.cairo");
         assert_eq!(virtual_file_markings, vec!["array_inline_macro"]);
     }
+
+    #[test]
+    fn test_remove_virtual_file_markings_malformed() {
+        let path = SourceFileFullPath("/path/to/project/lib.cairo[array_inline_macro".to_string());
+        let (path, virtual_file_markings) = path.remove_virtual_file_markings();
+        assert_eq!(path, "/path/to/project/lib.cairo[array_inline_macro");
+        assert_eq!(virtual_file_markings, Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_parse_virtual_file_path_bracketed_directories() {
+        let path = SourceFileFullPath("/path/[to]/pro[ject]/lib.cairo[assert_macro]".to_string());
+        let virtual_file_path = path.parse_virtual_file_path().unwrap();
+        assert_eq!(
+            virtual_file_path.real_path,
+            "/path/[to]/pro[ject]/lib.cairo"
+        );
+        assert_eq!(virtual_file_path.markings, vec!["assert_macro"]);
+    }
+
+    #[test]
+    fn test_parse_virtual_file_path_bracketed_file_name() {
+        let path = SourceFileFullPath("/path/to/project/[lib].cairo".to_string());
+        let virtual_file_path = path.parse_virtual_file_path().unwrap();
+        assert_eq!(virtual_file_path.real_path, "/path/to/project/[lib].cairo");
+        assert_eq!(virtual_file_path.markings, Vec::<&str>::new());
+
+        let path = SourceFileFullPath("/path/to/project/[lib]".to_string());
+        let virtual_file_path = path.parse_virtual_file_path().unwrap();
+        assert_eq!(virtual_file_path.real_path, "/path/to/project/[lib]");
+        assert_eq!(virtual_file_path.markings, Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_parse_virtual_file_path_errors() {
+        let unopened = SourceFileFullPath("/path/to/project/lib.cairo]".to_string());
+        assert_eq!(
+            unopened.parse_virtual_file_path(),
+            Err(VirtualFilePathError::UnopenedMarking(unopened.0.clone()))
+        );
+
+        let unclosed = SourceFileFullPath("/path/to/project/lib.cairo[a][b".to_string());
+        assert_eq!(
+            unclosed.parse_virtual_file_path(),
+            Err(VirtualFilePathError::UnclosedMarking(unclosed.0.clone()))
+        );
+
+        let empty = SourceFileFullPath("/path/to/project/lib.cairo[]".to_string());
+        assert_eq!(
+            empty.parse_virtual_file_path(),
+            Err(VirtualFilePathError::EmptyMarking(empty.0.clone()))
+        );
+    }
+
+    #[test]
+    fn test_virtual_file_path_display_round_trip() {
+        for path in [
+            "/path/to/project/lib.cairo",
+            "/path/[to]/project/lib.cairo[array_inline_macro][assert_macro]",
+            "C:\\path\\to\\lib.cairo[assert_macro]",
+        ] {
+            let source_file_full_path = SourceFileFullPath(path.to_string());
+            let virtual_file_path = source_file_full_path.parse_virtual_file_path().unwrap();
+            assert_eq!(virtual_file_path.to_string(), path);
+        }
+    }
 }
@@ -1,6 +1,7 @@
 use crate::annotations::coverage::{SourceCodeSpan, SourceFileFullPath};
-use crate::annotations::impl_helpers::impl_namespace;
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::annotations::impl_helpers::impl_versioned_namespace;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Versioned representation of Debugger Annotations.
@@ -17,13 +18,13 @@ pub enum VersionedDebuggerAnnotations {
 ///
 /// Needs `add-functions-debug-info = true`
 /// under `[profile.dev.cairo]` in the Scarb config to be generated.
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct DebuggerAnnotationsV1 {
     pub functions_info: HashMap<SierraFunctionId, FunctionDebugInfo>,
 }
 
 /// The debug info of a sierra function.
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct FunctionDebugInfo {
     /// Path to the user file the function comes from.
     pub function_file_path: SourceFileFullPath,
@@ -38,36 +39,16 @@ pub struct FunctionDebugInfo {
 type CairoVariableName = String;
 
 /// An id of a sierra function - equivalent to `id` field of [`cairo_lang_sierra::ids::FunctionId`].
-#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct SierraFunctionId(pub u64);
 
 /// An id of a sierra variable - equivalent to `id` field of [`cairo_lang_sierra::ids::VarId`].
-#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct SierraVarId(pub u64);
 
-// We can't use untagged enum here. See https://github.com/serde-rs/json/issues/1103
-impl Serialize for VersionedDebuggerAnnotations {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        match self {
-            VersionedDebuggerAnnotations::V1(v1) => v1.serialize(serializer),
-        }
-    }
-}
-
-impl<'de> Deserialize<'de> for VersionedDebuggerAnnotations {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        DebuggerAnnotationsV1::deserialize(deserializer).map(VersionedDebuggerAnnotations::V1)
-    }
-}
-
-impl_namespace!(
+impl_versioned_namespace!(
     "github.com/software-mansion-labs/cairo-debugger",
+    VersionedDebuggerAnnotations,
     DebuggerAnnotationsV1,
-    VersionedDebuggerAnnotations
+    "Debugger Annotations"
 );
@@ -8,4 +8,87 @@ macro_rules! impl_namespace {
     };
 }
 
+/// Implements the namespace, version detection and (de)serialization of a `Versioned*Annotations` enum
+/// with a single `V1` variant.
+macro_rules! impl_versioned_namespace {
+    ($namespace:expr, $versioned:ident, $v1:ident, $name:literal) => {
+        crate::annotations::impl_helpers::impl_namespace!($namespace, $v1);
+
+        impl $versioned {
+            #[doc = concat!("Versions of ", $name, " supported by this version of the crate.")]
+            pub const SUPPORTED_VERSIONS: &[u64] =
+                <Self as crate::annotations::traits::Namespace>::SUPPORTED_VERSIONS;
+
+            #[must_use]
+            pub fn version(&self) -> u64 {
+                match self {
+                    $versioned::V1(_) => 1,
+                }
+            }
+
+            /// Converts the annotations to the newest supported version.
+            #[must_use]
+            pub fn into_latest(self) -> $v1 {
+                match self {
+                    $versioned::V1(annotations) => annotations,
+                }
+            }
+        }
+
+        impl From<$v1> for $versioned {
+            fn from(annotations: $v1) -> Self {
+                $versioned::V1(annotations)
+            }
+        }
+
+        // We can't use untagged enum here. See https://github.com/serde-rs/json/issues/1103
+        impl serde::Serialize for $versioned {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                match self {
+                    $versioned::V1(v1) => serde::Serialize::serialize(v1, serializer),
+                }
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $versioned {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                crate::annotations::traits::deserialize_without_version::<Self, $v1, D>(
+                    deserializer,
+                )
+                .map($versioned::V1)
+            }
+        }
+
+        impl crate::annotations::schema::VersionedSchema for $versioned {
+            type Latest = $v1;
+        }
+
+        impl crate::annotations::traits::Namespace for $versioned {
+            const NAMESPACE: &'static str = $namespace;
+
+            fn from_value(
+                value: &serde_json::Value,
+                version: u64,
+            ) -> Result<Self, serde_json::Error> {
+                match version {
+                    1 => <$v1 as serde::Deserialize>::deserialize(value).map($versioned::V1),
+                    version => Err(serde::de::Error::custom(
+                        crate::annotations::traits::AnnotationsError::UnsupportedVersion {
+                            namespace: Self::NAMESPACE.into(),
+                            version,
+                        },
+                    )),
+                }
+            }
+        }
+    };
+}
+
 pub(crate) use impl_namespace;
+pub(crate) use impl_versioned_namespace;
@@ -1,9 +1,11 @@
+pub mod attribution;
 pub mod coverage;
 pub mod debugger;
 pub mod profiler;
+pub mod schema;
 pub mod type_names;
 
 mod impl_helpers;
 mod traits;
 
-pub use traits::{AnnotationsError, TryFromDebugInfo};
+pub use traits::{AnnotationsError, TryFromAnnotation, TryFromDebugInfo};
@@ -1,8 +1,13 @@
-use crate::annotations::impl_helpers::impl_namespace;
+use crate::annotations::attribution::AttributionPolicy;
+use crate::annotations::coverage::CoverageAnnotationsV1;
+use crate::annotations::impl_helpers::impl_versioned_namespace;
 use cairo_lang_sierra::program::StatementIdx;
 use derive_more::Display;
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::collections::HashMap;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use thiserror::Error;
 
 /// Versioned representation of Profiler Annotations.
 ///
@@ -24,40 +29,525 @@ pub enum VersionedProfilerAnnotations {
 ///
 /// Needs `unstable-add-statements-functions-debug-info = true`
 /// under `[profile.dev.cairo]` in the Scarb config to be generated.
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ProfilerAnnotationsV1 {
+    #[schemars(with = "HashMap<usize, Vec<FunctionName>>")]
     pub statements_functions: HashMap<StatementIdx, Vec<FunctionName>>,
 }
 
+impl ProfilerAnnotationsV1 {
+    /// Returns the functions the statement is attributed to under `policy`.
+    ///
+    /// Frames are selected based on the corresponding code locations from `coverage_annotations`,
+    /// so that coverage and profiling attribute statements the same way. If they are not available,
+    /// frames are selected by their position only, see [`AttributionPolicy::select_frames_by_position`].
+    #[must_use]
+    pub fn resolve_functions(
+        &self,
+        statement_idx: StatementIdx,
+        policy: AttributionPolicy,
+        coverage_annotations: Option<&CoverageAnnotationsV1>,
+    ) -> Vec<&FunctionName> {
+        let Some(function_names) = self.statements_functions.get(&statement_idx) else {
+            return Vec::new();
+        };
+
+        let frames = coverage_annotations
+            .and_then(|coverage_annotations| {
+                coverage_annotations
+                    .statements_code_locations
+                    .get(&statement_idx)
+            })
+            .filter(|code_locations| code_locations.len() == function_names.len())
+            .map_or_else(
+                || policy.select_frames_by_position(function_names.len()),
+                |code_locations| policy.select_frames(code_locations),
+            );
+
+        frames
+            .into_iter()
+            .map(|frame| &function_names[frame])
+            .collect()
+    }
+}
+
 /// The fully qualified Cairo path of the Cairo function.
 #[derive(
-    Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize, Display, Default,
+    Clone,
+    Debug,
+    Eq,
+    PartialEq,
+    Hash,
+    Ord,
+    PartialOrd,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    Display,
+    Default,
 )]
 pub struct FunctionName(pub String);
 
-// We can't use untagged enum here. See https://github.com/serde-rs/json/issues/1103
-impl Serialize for VersionedProfilerAnnotations {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        match self {
-            VersionedProfilerAnnotations::V1(v1) => v1.serialize(serializer),
+/// Prefix of closure type names, e.g. `{closure@src/lib.cairo:3:13}`.
+const CLOSURE_PREFIX: &str = "{closure";
+/// Suffix marker of functions generated by the compiler from expressions, e.g. loops: `my_package::sum[expr12]`.
+const GENERATED_EXPRESSION_MARKER: &str = "[expr";
+/// Prefix of functions generated by the compiler or plugins, e.g. `__wrapper__transfer` for contract entrypoints.
+const GENERATED_PREFIX: &str = "__";
+
+impl FunctionName {
+    /// Splits the name into the crate, module path, impl or trait, and function, with their generic arguments.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FunctionNameError`] if the name is empty or malformed.
+    ///
+    /// # Example
+    /// ```
+    /// use cairo_annotations::annotations::profiler::FunctionName;
+    ///
+    /// let function_name = FunctionName("core::array::ArrayImpl::<core::felt252>::append".to_string());
+    /// let parsed = function_name.parse().unwrap();
+    /// assert_eq!(parsed.crate_name, Some("core"));
+    /// assert_eq!(parsed.module_path, vec!["array"]);
+    /// assert_eq!(parsed.impl_segment.as_ref().unwrap().generic_args, vec!["core::felt252"]);
+    /// assert_eq!(parsed.function.name, "append");
+    /// assert_eq!(parsed.short_name(), "ArrayImpl::append");
+    /// ```
+    pub fn parse(&self) -> Result<ParsedFunctionName<'_>, FunctionNameError> {
+        ParsedFunctionName::parse(&self.0)
+    }
+
+    /// Returns the name with all generic arguments removed,
+    /// e.g. `core::array::ArrayImpl::append` for `core::array::ArrayImpl::<core::felt252>::append`.
+    ///
+    /// Names that cannot be parsed are returned unchanged.
+    #[must_use]
+    pub fn strip_generics(&self) -> FunctionName {
+        self.parse().map_or_else(
+            |_| self.clone(),
+            |parsed| FunctionName(parsed.without_generics().to_string()),
+        )
+    }
+}
+
+/// Groups monomorphizations of the same generic function under its name with generics stripped,
+/// see [`FunctionName::strip_generics`].
+#[must_use]
+pub fn collapse_monomorphizations<'a>(
+    function_names: impl IntoIterator<Item = &'a FunctionName>,
+) -> BTreeMap<FunctionName, BTreeSet<&'a FunctionName>> {
+    let mut collapsed: BTreeMap<FunctionName, BTreeSet<&'a FunctionName>> = BTreeMap::new();
+    for function_name in function_names {
+        collapsed
+            .entry(function_name.strip_generics())
+            .or_default()
+            .insert(function_name);
+    }
+    collapsed
+}
+
+/// A [`FunctionName`] split into its components.
+///
+/// Cairo names don't tell modules apart from impls and traits, so the segment right before the function
+/// is considered an impl or trait if it starts with an uppercase letter (as impls and traits conventionally do)
+/// or has generic arguments.
+///
+/// Displaying it renders the full name back.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ParsedFunctionName<'a> {
+    /// Name of the crate, `None` if the name consists of the function only.
+    pub crate_name: Option<&'a str>,
+    /// Modules between the crate and the impl or function.
+    pub module_path: Vec<&'a str>,
+    /// Impl or trait the function belongs to.
+    pub impl_segment: Option<PathSegment<'a>>,
+    /// The function itself.
+    pub function: PathSegment<'a>,
+}
+
+/// A segment of a [`ParsedFunctionName`] which can have generic arguments, i.e. an impl, a trait or a function.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PathSegment<'a> {
+    pub name: &'a str,
+    /// Generic arguments, e.g. `["core::felt252", "()"]` for `Impl::<core::felt252, ()>`.
+    pub generic_args: Vec<&'a str>,
+}
+
+/// Error that can occur when parsing a [`FunctionName`].
+#[derive(Clone, Debug, Eq, PartialEq, Error)]
+pub enum FunctionNameError {
+    /// Error indicating that the function name is empty.
+    #[error("Function name is empty")]
+    Empty,
+
+    /// Error indicating that brackets in the function name do not match.
+    #[error("Unbalanced brackets in function name: {0}")]
+    UnbalancedBrackets(String),
+
+    /// Error indicating that the function name has an empty segment, e.g. `core::::append`.
+    #[error("Empty segment in function name: {0}")]
+    EmptySegment(String),
+
+    /// Error indicating that generic arguments are placed where they are not allowed,
+    /// e.g. after the crate or at the beginning of the name.
+    #[error("Misplaced generic arguments in function name: {0}")]
+    MisplacedGenericArgs(String),
+}
+
+impl<'a> ParsedFunctionName<'a> {
+    pub(crate) fn parse(name: &'a str) -> Result<Self, FunctionNameError> {
+        if name.is_empty() {
+            return Err(FunctionNameError::Empty);
+        }
+
+        let mut segments: Vec<PathSegment<'a>> = Vec::new();
+        for part in split_top_level(name, "::", name)? {
+            if let Some(generic_args) = part
+                .strip_prefix('<')
+                .and_then(|part| part.strip_suffix('>'))
+            {
+                let segment = segments
+                    .last_mut()
+                    .filter(|segment| segment.generic_args.is_empty())
+                    .ok_or_else(|| FunctionNameError::MisplacedGenericArgs(name.to_string()))?;
+                segment.generic_args = split_top_level(generic_args, ",", name)?
+                    .into_iter()
+                    .map(str::trim)
+                    .collect();
+            } else if part.is_empty() {
+                return Err(FunctionNameError::EmptySegment(name.to_string()));
+            } else {
+                segments.push(PathSegment {
+                    name: part,
+                    generic_args: Vec::new(),
+                });
+            }
+        }
+
+        let function = segments
+            .pop()
+            .unwrap_or_else(|| unreachable!("name is not empty"));
+        let is_impl = |segment: &PathSegment<'_>| {
+            !segment.generic_args.is_empty()
+                || segment.name.starts_with(|c: char| c.is_ascii_uppercase())
+        };
+        // The first segment is always the crate, so it is never taken as an impl.
+        let impl_segment = if segments.len() > 1 && segments.last().is_some_and(is_impl) {
+            segments.pop()
+        } else {
+            None
+        };
+
+        if segments
+            .iter()
+            .any(|segment| !segment.generic_args.is_empty())
+        {
+            return Err(FunctionNameError::MisplacedGenericArgs(name.to_string()));
+        }
+        let mut segments = segments.into_iter().map(|segment| segment.name);
+        let crate_name = segments.next();
+        let module_path = segments.collect();
+
+        Ok(Self {
+            crate_name,
+            module_path,
+            impl_segment,
+            function,
+        })
+    }
+
+    /// Crate and module path joined with `::`, e.g. `core::array`. Useful for grouping functions by module.
+    #[must_use]
+    pub fn module(&self) -> String {
+        self.crate_name
+            .into_iter()
+            .chain(self.module_path.iter().copied())
+            .collect::<Vec<_>>()
+            .join("::")
+    }
+
+    /// Human-friendly name without the module path and generic arguments, e.g. `ArrayImpl::append`.
+    #[must_use]
+    pub fn short_name(&self) -> String {
+        match &self.impl_segment {
+            Some(impl_segment) => format!("{}::{}", impl_segment.name, self.function.name),
+            None => self.function.name.to_string(),
         }
     }
+
+    /// Returns the name with generic arguments of the impl and the function removed.
+    #[must_use]
+    pub fn without_generics(&self) -> Self {
+        let strip = |segment: &PathSegment<'a>| PathSegment {
+            name: segment.name,
+            generic_args: Vec::new(),
+        };
+        Self {
+            crate_name: self.crate_name,
+            module_path: self.module_path.clone(),
+            impl_segment: self.impl_segment.as_ref().map(strip),
+            function: strip(&self.function),
+        }
+    }
+
+    /// Whether the function is a closure body or an implementation of a trait for a closure type,
+    /// e.g. `core::ops::function::FnOnceImpl::<{closure@src/lib.cairo:3:13}>::call`.
+    #[must_use]
+    pub fn is_closure(&self) -> bool {
+        let impl_generic_args = self
+            .impl_segment
+            .iter()
+            .flat_map(|impl_segment| &impl_segment.generic_args);
+        self.module_path
+            .iter()
+            .chain(
+                self.impl_segment
+                    .as_ref()
+                    .map(|impl_segment| &impl_segment.name),
+            )
+            .chain([&self.function.name])
+            .chain(impl_generic_args)
+            .any(|name| name.starts_with(CLOSURE_PREFIX))
+    }
+
+    /// Whether the function was generated by the compiler or a plugin rather than written by the user,
+    /// e.g. a loop body `my_package::sum[expr12]` or a contract entrypoint wrapper `__wrapper__transfer`.
+    #[must_use]
+    pub fn is_generated(&self) -> bool {
+        self.function.name.contains(GENERATED_EXPRESSION_MARKER)
+            || self
+                .module_path
+                .iter()
+                .chain(
+                    self.impl_segment
+                        .as_ref()
+                        .map(|impl_segment| &impl_segment.name),
+                )
+                .chain([&self.function.name])
+                .any(|name| name.starts_with(GENERATED_PREFIX))
+    }
+}
+
+impl fmt::Display for ParsedFunctionName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in self.crate_name.iter().chain(&self.module_path) {
+            write!(f, "{segment}::")?;
+        }
+        if let Some(impl_segment) = &self.impl_segment {
+            write!(f, "{impl_segment}::")?;
+        }
+        write!(f, "{}", self.function)
+    }
+}
+
+impl fmt::Display for PathSegment<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.generic_args.is_empty() {
+            write!(f, "::<{}>", self.generic_args.join(", "))?;
+        }
+        Ok(())
+    }
 }
 
-impl<'de> Deserialize<'de> for VersionedProfilerAnnotations {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        ProfilerAnnotationsV1::deserialize(deserializer).map(VersionedProfilerAnnotations::V1)
+/// Splits `text` on `separator`, skipping separators nested in brackets.
+/// `name` is the whole function name, used for errors.
+fn split_top_level<'a>(
+    text: &'a str,
+    separator: &str,
+    name: &str,
+) -> Result<Vec<&'a str>, FunctionNameError> {
+    let unbalanced = || FunctionNameError::UnbalancedBrackets(name.to_string());
+    let mut parts = Vec::new();
+    let mut open_brackets = Vec::new();
+    let mut part_start = 0;
+
+    for (index, c) in text.char_indices() {
+        match c {
+            '<' | '(' | '[' | '{' => open_brackets.push(c),
+            '>' | ')' | ']' | '}' => {
+                let opening = match c {
+                    '>' => '<',
+                    ')' => '(',
+                    ']' => '[',
+                    _ => '{',
+                };
+                if open_brackets.pop() != Some(opening) {
+                    return Err(unbalanced());
+                }
+            }
+            _ if open_brackets.is_empty()
+                && index >= part_start
+                && text[index..].starts_with(separator) =>
+            {
+                parts.push(&text[part_start..index]);
+                part_start = index + separator.len();
+            }
+            _ => {}
+        }
+    }
+    if !open_brackets.is_empty() {
+        return Err(unbalanced());
     }
+    parts.push(&text[part_start..]);
+
+    Ok(parts)
 }
 
-impl_namespace!(
+impl_versioned_namespace!(
     "github.com/software-mansion/cairo-profiler",
+    VersionedProfilerAnnotations,
     ProfilerAnnotationsV1,
-    VersionedProfilerAnnotations
+    "Profiler Annotations"
 );
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn function_name(name: &str) -> FunctionName {
+        FunctionName(name.to_string())
+    }
+
+    #[test]
+    fn test_parse() {
+        let name = function_name(
+            "core::result::ResultTraitImpl::<(), core::array::Array::<core::felt252>>::expect::<core::array::ArrayDrop::<core::felt252>>",
+        );
+        let parsed = name.parse().unwrap();
+
+        assert_eq!(parsed.crate_name, Some("core"));
+        assert_eq!(parsed.module_path, vec!["result"]);
+        assert_eq!(
+            parsed.impl_segment,
+            Some(PathSegment {
+                name: "ResultTraitImpl",
+                generic_args: vec!["()", "core::array::Array::<core::felt252>"],
+            })
+        );
+        assert_eq!(
+            parsed.function,
+            PathSegment {
+                name: "expect",
+                generic_args: vec!["core::array::ArrayDrop::<core::felt252>"],
+            }
+        );
+        assert_eq!(parsed.module(), "core::result");
+        assert_eq!(parsed.short_name(), "ResultTraitImpl::expect");
+        assert_eq!(parsed.to_string(), name.0);
+    }
+
+    #[test]
+    fn test_parse_without_impl() {
+        let name = function_name("my_package::math::utils::sum");
+        let parsed = name.parse().unwrap();
+        assert_eq!(parsed.crate_name, Some("my_package"));
+        assert_eq!(parsed.module_path, vec!["math", "utils"]);
+        assert_eq!(parsed.impl_segment, None);
+        assert_eq!(parsed.short_name(), "sum");
+
+        let name = function_name("main");
+        let parsed = name.parse().unwrap();
+        assert_eq!(parsed.crate_name, None);
+        assert_eq!(parsed.module(), "");
+        assert_eq!(parsed.function.name, "main");
+
+        let name = function_name("Package::main");
+        let parsed = name.parse().unwrap();
+        assert_eq!(parsed.crate_name, Some("Package"));
+        assert_eq!(parsed.impl_segment, None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(function_name("").parse(), Err(FunctionNameError::Empty));
+        assert_eq!(
+            function_name("core::ArrayImpl::<felt252::append").parse(),
+            Err(FunctionNameError::UnbalancedBrackets(
+                "core::ArrayImpl::<felt252::append".to_string()
+            ))
+        );
+        assert_eq!(
+            function_name("core::ArrayImpl::<felt252)>::append").parse(),
+            Err(FunctionNameError::UnbalancedBrackets(
+                "core::ArrayImpl::<felt252)>::append".to_string()
+            ))
+        );
+        assert_eq!(
+            function_name("core::::append").parse(),
+            Err(FunctionNameError::EmptySegment(
+                "core::::append".to_string()
+            ))
+        );
+        assert_eq!(
+            function_name("core::<felt252>::array::append").parse(),
+            Err(FunctionNameError::MisplacedGenericArgs(
+                "core::<felt252>::array::append".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_strip_generics() {
+        assert_eq!(
+            function_name("core::array::ArrayImpl::<core::felt252>::append").strip_generics(),
+            function_name("core::array::ArrayImpl::append")
+        );
+        assert_eq!(
+            function_name("core::ArrayImpl::<felt252").strip_generics(),
+            function_name("core::ArrayImpl::<felt252")
+        );
+    }
+
+    #[test]
+    fn test_collapse_monomorphizations() {
+        let names = [
+            function_name("core::array::ArrayImpl::<core::felt252>::append"),
+            function_name("core::array::ArrayImpl::<core::integer::u8>::append"),
+            function_name("core::array::ArrayImpl::<core::felt252>::append"),
+            function_name("my_package::main"),
+        ];
+
+        let collapsed = collapse_monomorphizations(&names);
+
+        assert_eq!(
+            collapsed,
+            BTreeMap::from([
+                (
+                    function_name("core::array::ArrayImpl::append"),
+                    BTreeSet::from([&names[0], &names[1]])
+                ),
+                (
+                    function_name("my_package::main"),
+                    BTreeSet::from([&names[3]])
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_closures_and_generated_functions() {
+        let is_closure = |name| function_name(name).parse().unwrap().is_closure();
+        let is_generated = |name| function_name(name).parse().unwrap().is_generated();
+
+        assert!(is_closure(
+            "core::ops::function::FnOnceImpl::<{closure@src/lib.cairo:3:13}>::call"
+        ));
+        assert!(is_closure(
+            "my_package::main::{closure@src/lib.cairo:3:13}::call"
+        ));
+        assert!(!is_closure(
+            "core::option::OptionTraitImpl::<core::felt252>::map::<{closure@src/lib.cairo:3:13}>"
+        ));
+
+        assert!(is_generated("my_package::sum[expr12]"));
+        assert!(is_generated(
+            "my_package::contract::__wrapper__ContractImpl__transfer"
+        ));
+        assert!(is_generated("my_package::contract::__external::transfer"));
+        assert!(!is_generated(
+            "my_package::contract::ContractImpl::transfer"
+        ));
+    }
+}
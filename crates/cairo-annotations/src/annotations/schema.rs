@@ -0,0 +1,383 @@
+use crate::annotations::coverage::VersionedCoverageAnnotations;
+use crate::annotations::debugger::VersionedDebuggerAnnotations;
+use crate::annotations::profiler::VersionedProfilerAnnotations;
+use crate::annotations::traits::Namespace;
+use crate::annotations::type_names::VersionedTypeNamesAnnotations;
+#[cfg(feature = "schema-validation")]
+use cairo_lang_sierra::debug_info::DebugInfo;
+#[cfg(feature = "schema-validation")]
+use jsonschema::paths::LocationSegment;
+use schemars::{JsonSchema, Schema, schema_for};
+use serde_json::Value;
+#[cfg(feature = "schema-validation")]
+use std::fmt::{self, Write};
+#[cfg(feature = "schema-validation")]
+use thiserror::Error;
+
+/// JSON Schema of annotations stored under a namespace of sierra [`DebugInfo`](cairo_lang_sierra::debug_info::DebugInfo).
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnnotationSchema {
+    /// Namespace URI of the annotations, e.g. `github.com/software-mansion/cairo-coverage`.
+    pub namespace: &'static str,
+    pub version: u64,
+    /// The schema, with the namespace and version in the `x-namespace` and `x-version` keywords.
+    pub schema: Schema,
+}
+
+/// A place where an annotation value does not match its schema.
+#[cfg(feature = "schema-validation")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SchemaViolation {
+    /// Path of the value, e.g. `statements_code_locations.12[0][1].start.line`.
+    /// Empty for the whole annotation value.
+    pub path: String,
+    pub message: String,
+}
+
+/// Enum representing the possible errors that can occur when validating annotations.
+#[cfg(feature = "schema-validation")]
+#[derive(Clone, Debug, Eq, PartialEq, Error)]
+pub enum AnnotationValidationError {
+    /// Error indicating that there is no schema for the namespace.
+    #[error("Unknown namespace: {0}")]
+    UnknownNamespace(String),
+
+    /// Error indicating that the schema of the namespace is not a valid JSON Schema.
+    #[error("Invalid schema of namespace {namespace}: {message}")]
+    InvalidSchema { namespace: String, message: String },
+
+    /// Error indicating that the annotations do not match the schema of their namespace.
+    #[error("Invalid annotations in namespace {namespace}: {}", display_violations(.violations))]
+    Invalid {
+        namespace: String,
+        violations: Vec<SchemaViolation>,
+    },
+}
+
+/// Versioned annotations with a JSON Schema of their newest version.
+pub(crate) trait VersionedSchema: Namespace {
+    /// The newest version of the annotations, as returned by `into_latest`.
+    type Latest: JsonSchema;
+}
+
+type SchemaBuilder = fn() -> AnnotationSchema;
+
+/// Namespaces of all annotations, with functions building their schemas.
+const SCHEMAS: [(&str, SchemaBuilder); 4] = [
+    (
+        VersionedCoverageAnnotations::NAMESPACE,
+        annotation_schema_of::<VersionedCoverageAnnotations>,
+    ),
+    (
+        VersionedProfilerAnnotations::NAMESPACE,
+        annotation_schema_of::<VersionedProfilerAnnotations>,
+    ),
+    (
+        VersionedDebuggerAnnotations::NAMESPACE,
+        annotation_schema_of::<VersionedDebuggerAnnotations>,
+    ),
+    (
+        VersionedTypeNamesAnnotations::NAMESPACE,
+        annotation_schema_of::<VersionedTypeNamesAnnotations>,
+    ),
+];
+
+/// Returns schemas of the newest versions of all annotation namespaces.
+#[must_use]
+pub fn annotation_schemas() -> Vec<AnnotationSchema> {
+    SCHEMAS
+        .iter()
+        .map(|(_, annotation_schema)| annotation_schema())
+        .collect()
+}
+
+/// Returns the schema of the newest version of annotations stored under `namespace`.
+#[must_use]
+pub fn annotation_schema(namespace: &str) -> Option<AnnotationSchema> {
+    SCHEMAS
+        .iter()
+        .find(|(schema_namespace, _)| *schema_namespace == namespace)
+        .map(|(_, annotation_schema)| annotation_schema())
+}
+
+/// Validates annotations stored under `namespace` against the schema of the namespace.
+///
+/// # Errors
+///
+/// Returns an error if the namespace is not known or the value does not match the schema.
+/// All violations are reported, each with the path of the invalid value.
+#[cfg(feature = "schema-validation")]
+pub fn validate_annotation(
+    namespace: &str,
+    value: &Value,
+) -> Result<(), AnnotationValidationError> {
+    annotation_schema(namespace)
+        .ok_or_else(|| AnnotationValidationError::UnknownNamespace(namespace.to_string()))?
+        .validate(value)
+}
+
+/// Validates annotations of all known namespaces present in `debug_info`.
+/// Annotations of other namespaces are skipped.
+///
+/// Returns errors of all namespaces with invalid annotations.
+#[cfg(feature = "schema-validation")]
+#[must_use]
+pub fn validate_debug_info(debug_info: &DebugInfo) -> Vec<AnnotationValidationError> {
+    validate_debug_info_with(&annotation_schemas(), debug_info)
+}
+
+/// Validates annotations present in `debug_info` against `schemas`, e.g. built once with [`annotation_schemas`]
+/// and reused for many artifacts. Annotations of namespaces without a schema are skipped.
+///
+/// Returns errors of all namespaces with invalid annotations.
+#[cfg(feature = "schema-validation")]
+#[must_use]
+pub fn validate_debug_info_with(
+    schemas: &[AnnotationSchema],
+    debug_info: &DebugInfo,
+) -> Vec<AnnotationValidationError> {
+    schemas
+        .iter()
+        .filter_map(|schema| {
+            let value = debug_info.annotations.get(schema.namespace)?;
+            schema.validate(value).err()
+        })
+        .collect()
+}
+
+#[cfg(feature = "schema-validation")]
+impl AnnotationSchema {
+    /// Validates annotations against the schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the schema is invalid or the value does not match it.
+    /// All violations are reported, each with the path of the invalid value.
+    pub fn validate(&self, value: &Value) -> Result<(), AnnotationValidationError> {
+        let validator = jsonschema::validator_for(self.schema.as_value()).map_err(|error| {
+            AnnotationValidationError::InvalidSchema {
+                namespace: self.namespace.to_string(),
+                message: error.to_string(),
+            }
+        })?;
+        let violations: Vec<_> = validator
+            .iter_errors(value)
+            .map(|error| SchemaViolation {
+                path: display_path(value, error.instance_path().iter()),
+                message: error.to_string(),
+            })
+            .collect();
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(AnnotationValidationError::Invalid {
+                namespace: self.namespace.to_string(),
+                violations,
+            })
+        }
+    }
+}
+
+fn annotation_schema_of<T: VersionedSchema>() -> AnnotationSchema {
+    let version = T::SUPPORTED_VERSIONS
+        .iter()
+        .copied()
+        .max()
+        .expect("annotations have at least one supported version");
+    let mut schema = schema_for!(T::Latest);
+    schema.insert("x-namespace".to_string(), Value::from(T::NAMESPACE));
+    schema.insert("x-version".to_string(), Value::from(version));
+    AnnotationSchema {
+        namespace: T::NAMESPACE,
+        version,
+        schema,
+    }
+}
+
+/// Formats the location of a value in `value` like paths of trace issues, e.g. `statements_code_locations.12[0]`.
+///
+/// Segments of the location do not tell array indices from numeric object keys, so they are told apart by `value`.
+#[cfg(feature = "schema-validation")]
+fn display_path<'a>(value: &Value, segments: impl Iterator<Item = LocationSegment<'a>>) -> String {
+    let mut path = String::new();
+    let mut current = Some(value);
+    for segment in segments {
+        let segment = segment.to_string();
+        if let Some(Value::Array(items)) = current {
+            let _ = write!(path, "[{segment}]");
+            current = segment
+                .parse()
+                .ok()
+                .and_then(|index: usize| items.get(index));
+        } else {
+            if !path.is_empty() {
+                path.push('.');
+            }
+            path.push_str(&segment);
+            current = current.and_then(|object| object.get(&segment));
+        }
+    }
+    path
+}
+
+#[cfg(feature = "schema-validation")]
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+#[cfg(feature = "schema-validation")]
+fn display_violations(violations: &[SchemaViolation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(feature = "schema-validation")]
+    use serde_json::json;
+    use std::fs;
+
+    #[cfg(feature = "schema-validation")]
+    const COVERAGE_NAMESPACE: &str = "github.com/software-mansion/cairo-coverage";
+
+    #[test]
+    fn test_schemas_are_up_to_date() {
+        for (annotation_schema, name) in
+            annotation_schemas()
+                .iter()
+                .zip(["coverage", "profiler", "debugger", "type_names"])
+        {
+            let file_name = format!("{name}_annotations_v{}.json", annotation_schema.version);
+            let expected = fs::read_to_string(format!("schemas/{file_name}")).unwrap();
+            assert_eq!(
+                format!(
+                    "{}\n",
+                    serde_json::to_string_pretty(&annotation_schema.schema).unwrap()
+                ),
+                expected,
+                "{file_name} is outdated"
+            );
+        }
+    }
+
+    #[test]
+    fn test_annotation_schema() {
+        let schema = annotation_schema(VersionedCoverageAnnotations::NAMESPACE).unwrap();
+        assert_eq!(
+            schema.version,
+            *VersionedCoverageAnnotations::SUPPORTED_VERSIONS
+                .last()
+                .unwrap()
+        );
+        assert_eq!(
+            schema.schema.get("x-namespace"),
+            Some(&Value::from(VersionedCoverageAnnotations::NAMESPACE))
+        );
+        assert_eq!(annotation_schema("github.com/unknown"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "schema-validation")]
+    fn test_validate_annotation() {
+        let location = json!({ "line": 1, "col": 0 });
+        let valid = json!({
+            "statements_code_locations": {
+                "0": [["src/lib.cairo", { "start": location, "end": location }, false]],
+            }
+        });
+        assert_eq!(validate_annotation(COVERAGE_NAMESPACE, &valid), Ok(()));
+
+        // Emitted by `scarb` versions before `2.11.0`, without the macro-generated flag.
+        let without_macro_flag = json!({
+            "statements_code_locations": {
+                "0": [["src/lib.cairo", { "start": location, "end": location }]],
+            }
+        });
+        assert_eq!(
+            validate_annotation(COVERAGE_NAMESPACE, &without_macro_flag),
+            Ok(())
+        );
+
+        let invalid = json!({
+            "statements_code_locations": {
+                "0": [["src/lib.cairo", { "start": location, "end": { "line": -1 } }]],
+                "1": [["src/lib.cairo"]],
+            }
+        });
+        let Err(AnnotationValidationError::Invalid { violations, .. }) =
+            validate_annotation(COVERAGE_NAMESPACE, &invalid)
+        else {
+            panic!("Expected invalid annotations");
+        };
+        let paths: Vec<&str> = violations
+            .iter()
+            .map(|violation| violation.path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "statements_code_locations.0[0][1].end",
+                "statements_code_locations.0[0][1].end.line",
+                "statements_code_locations.1[0]",
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "schema-validation")]
+    fn test_validate_debug_info_with() {
+        let schemas = annotation_schemas();
+        let mut debug_info = DebugInfo::default();
+        debug_info.annotations.insert(
+            COVERAGE_NAMESPACE.into(),
+            json!({ "statements_code_locations": [] }),
+        );
+        debug_info
+            .annotations
+            .insert("github.com/unknown".into(), json!({}));
+
+        let errors = validate_debug_info_with(&schemas, &debug_info);
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            AnnotationValidationError::Invalid { namespace, .. } if namespace == COVERAGE_NAMESPACE
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "schema-validation")]
+    fn test_validate_invalid_schema() {
+        let annotation_schema = AnnotationSchema {
+            namespace: COVERAGE_NAMESPACE,
+            version: 1,
+            schema: json!({ "type": 1 }).try_into().unwrap(),
+        };
+        assert!(matches!(
+            annotation_schema.validate(&json!({})),
+            Err(AnnotationValidationError::InvalidSchema { .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "schema-validation")]
+    fn test_validate_unknown_namespace() {
+        assert_eq!(
+            validate_annotation("github.com/unknown", &json!({})),
+            Err(AnnotationValidationError::UnknownNamespace(
+                "github.com/unknown".to_string()
+            ))
+        );
+    }
+}
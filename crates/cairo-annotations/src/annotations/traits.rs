@@ -1,5 +1,10 @@
 use cairo_lang_sierra::debug_info::DebugInfo;
-use serde::de::DeserializeOwned;
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, de};
+use serde_json::Value;
+use std::fmt;
+use std::marker::PhantomData;
 use thiserror::Error;
 
 pub trait TryFromDebugInfo: Sized {
@@ -12,6 +17,19 @@ pub trait TryFromDebugInfo: Sized {
     fn try_from_debug_info(sierra_debug_info: &DebugInfo) -> Result<Self, Self::Error>;
 }
 
+pub trait TryFromAnnotation: Sized {
+    type Error: std::error::Error;
+
+    /// Attempt to create an instance of the implementing type from the value of its namespace in sierra
+    /// debug info annotations, e.g. when the sierra file was read as a [`Value`] instead of [`DebugInfo`].
+    ///
+    /// The value is deserialized in place, without cloning it.
+    /// # Errors
+    ///
+    /// This function will return an error if the conversion from the value to the implementing type fails.
+    fn try_from_annotation(annotation: &Value) -> Result<Self, Self::Error>;
+}
+
 /// Enum representing the possible errors that can occur when trying to create an annotation from sierra debug information.
 #[derive(Debug, Error)]
 pub enum AnnotationsError {
@@ -22,10 +40,139 @@ pub enum AnnotationsError {
     /// Error indicating that the deserialization of the annotation failed.
     #[error("Deserialization error: {0}")]
     DeserializationError(#[from] serde_json::Error),
+
+    /// Error indicating that the `version` field of the annotations is not a positive integer.
+    #[error("Invalid version of annotations in namespace {namespace}: {version}")]
+    InvalidVersion { namespace: String, version: Value },
+
+    /// Error indicating that the annotations were generated in a version not supported by this version of the crate,
+    /// most likely by a newer compiler.
+    #[error("Unsupported version of annotations in namespace {namespace}: {version}")]
+    UnsupportedVersion { namespace: String, version: u64 },
 }
 
 pub(crate) trait Namespace {
     const NAMESPACE: &'static str;
+
+    /// Versions of the annotations the type can be deserialized from.
+    const SUPPORTED_VERSIONS: &'static [u64] = &[1];
+
+    /// Deserializes annotations of a supported `version` from a borrowed value.
+    fn from_value(value: &Value, version: u64) -> Result<Self, serde_json::Error>
+    where
+        Self: Sized + DeserializeOwned,
+    {
+        debug_assert_eq!(version, 1);
+        Self::deserialize(value)
+    }
+}
+
+/// Returns the version of annotations of the namespace of `T`.
+///
+/// Versions after V1 are marked with the `version` field, annotations without it are V1.
+pub(crate) fn annotations_version<T: Namespace>(value: &Value) -> Result<u64, AnnotationsError> {
+    check_version::<T>(value.get("version"))
+}
+
+/// Validates the `version` field of annotations of the namespace of `T`, if present.
+///
+/// Versions are positive integers, so `0` is an invalid version rather than an unsupported one.
+fn check_version<T: Namespace>(version: Option<&Value>) -> Result<u64, AnnotationsError> {
+    let Some(version) = version else {
+        return Ok(1);
+    };
+    let version = version
+        .as_u64()
+        .filter(|&version| version > 0)
+        .ok_or_else(|| AnnotationsError::InvalidVersion {
+            namespace: T::NAMESPACE.into(),
+            version: version.clone(),
+        })?;
+    if T::SUPPORTED_VERSIONS.contains(&version) {
+        Ok(version)
+    } else {
+        Err(AnnotationsError::UnsupportedVersion {
+            namespace: T::NAMESPACE.into(),
+            version,
+        })
+    }
+}
+
+/// Deserializes annotations of the namespace of `N` as `T`, streaming their fields and checking the `version`
+/// field on the way, without collecting the annotations into a [`Value`] first.
+///
+/// `T` must be the only version in `N::SUPPORTED_VERSIONS`. If the `version` field comes after fields that are
+/// invalid for `T`, the deserialization error is reported instead of the unsupported version.
+pub(crate) fn deserialize_without_version<'de, N, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    N: Namespace,
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    struct AnnotationsVisitor<N, T>(PhantomData<(N, T)>);
+
+    impl<'de, N: Namespace, T: Deserialize<'de>> Visitor<'de> for AnnotationsVisitor<N, T> {
+        type Value = T;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "annotations of namespace {}", N::NAMESPACE)
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<T, A::Error> {
+            T::deserialize(MapAccessDeserializer::new(WithoutVersion::<N, A> {
+                map,
+                namespace: PhantomData,
+            }))
+        }
+    }
+
+    deserializer.deserialize_map(AnnotationsVisitor::<N, T>(PhantomData))
+}
+
+/// Fields of annotations without the `version` field, which is checked when it is reached.
+struct WithoutVersion<N, A> {
+    map: A,
+    namespace: PhantomData<N>,
+}
+
+impl<'de, N: Namespace, A: MapAccess<'de>> MapAccess<'de> for WithoutVersion<N, A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        while let Some(key) = self.map.next_key::<String>()? {
+            if key == "version" {
+                let version: Value = self.map.next_value()?;
+                check_version::<N>(Some(&version)).map_err(de::Error::custom)?;
+            } else {
+                return seed
+                    .deserialize(IntoDeserializer::<Self::Error>::into_deserializer(key))
+                    .map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.map.next_value_seed(seed)
+    }
+}
+
+impl<T> TryFromAnnotation for T
+where
+    T: Namespace + DeserializeOwned,
+{
+    type Error = AnnotationsError;
+
+    fn try_from_annotation(annotation: &Value) -> Result<Self, Self::Error> {
+        let version = annotations_version::<Self>(annotation)?;
+        Self::from_value(annotation, version).map_err(AnnotationsError::DeserializationError)
+    }
 }
 
 impl<T> TryFromDebugInfo for T
@@ -40,6 +187,119 @@ where
             .get(Self::NAMESPACE)
             .ok_or_else(|| AnnotationsError::MissingNamespace(Self::NAMESPACE.into()))?;
 
-        serde_json::from_value(value.clone()).map_err(AnnotationsError::DeserializationError)
+        Self::try_from_annotation(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::annotations::coverage::VersionedCoverageAnnotations;
+    use serde_json::json;
+
+    fn debug_info(annotations: Value) -> DebugInfo {
+        DebugInfo {
+            annotations: [(
+                VersionedCoverageAnnotations::NAMESPACE.to_string(),
+                annotations,
+            )]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_versions() {
+        let annotations = VersionedCoverageAnnotations::try_from_debug_info(&debug_info(
+            json!({ "statements_code_locations": {} }),
+        ))
+        .unwrap();
+        assert_eq!(annotations.version(), 1);
+
+        let explicit_v1 = VersionedCoverageAnnotations::try_from_debug_info(&debug_info(
+            json!({ "version": 1, "statements_code_locations": {} }),
+        ))
+        .unwrap();
+        assert_eq!(explicit_v1, annotations);
+    }
+
+    #[test]
+    fn test_try_from_annotation() {
+        let value = json!({ "statements_code_locations": { "0": [] } });
+        let annotations = VersionedCoverageAnnotations::try_from_annotation(&value).unwrap();
+        assert_eq!(
+            annotations,
+            VersionedCoverageAnnotations::try_from_debug_info(&debug_info(value)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let error = VersionedCoverageAnnotations::try_from_debug_info(&debug_info(
+            json!({ "version": 2, "statements": [] }),
+        ))
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            AnnotationsError::UnsupportedVersion { version: 2, .. }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "Unsupported version of annotations in namespace github.com/software-mansion/cairo-coverage: 2"
+        );
+
+        let error = VersionedCoverageAnnotations::try_from_debug_info(&debug_info(
+            json!({ "version": "2", "statements_code_locations": {} }),
+        ))
+        .unwrap_err();
+        assert!(matches!(error, AnnotationsError::InvalidVersion { .. }));
+
+        let error = VersionedCoverageAnnotations::try_from_debug_info(&debug_info(
+            json!({ "version": 0, "statements_code_locations": {} }),
+        ))
+        .unwrap_err();
+        assert!(matches!(error, AnnotationsError::InvalidVersion { .. }));
+
+        // A corrupted V1 is still reported as a deserialization error.
+        let error = VersionedCoverageAnnotations::try_from_debug_info(&debug_info(
+            json!({ "statements_code_locations": [] }),
+        ))
+        .unwrap_err();
+        assert!(matches!(error, AnnotationsError::DeserializationError(_)));
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let annotations: VersionedCoverageAnnotations =
+            serde_json::from_str(r#"{ "statements_code_locations": { "3": [] }, "version": 1 }"#)
+                .unwrap();
+        assert_eq!(
+            annotations,
+            VersionedCoverageAnnotations::try_from_annotation(
+                &json!({ "statements_code_locations": { "3": [] } })
+            )
+            .unwrap()
+        );
+
+        let error = serde_json::from_str::<VersionedCoverageAnnotations>(
+            r#"{ "version": 2, "statements": [] }"#,
+        )
+        .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("Unsupported version of annotations in namespace github.com/software-mansion/cairo-coverage: 2")
+        );
+
+        let error = serde_json::from_str::<VersionedCoverageAnnotations>(
+            r#"{ "statements_code_locations": {}, "version": 0 }"#,
+        )
+        .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("Invalid version of annotations in namespace github.com/software-mansion/cairo-coverage: 0")
+        );
     }
 }
@@ -1,5 +1,6 @@
-use crate::annotations::impl_helpers::impl_namespace;
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::annotations::impl_helpers::impl_versioned_namespace;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Versioned representation of Type Names Annotations.
@@ -14,14 +15,14 @@ pub enum VersionedTypeNamesAnnotations {
 ///
 /// Needs `add-types-debug-info = true`
 /// under `[profile.dev.cairo]` in the Scarb config to be generated.
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct TypeNamesAnnotationsV1 {
     pub structs: HashMap<SierraTypeId, StructInfo>,
     pub enums: HashMap<SierraTypeId, EnumInfo>,
 }
 
 /// Debug info of a Sierra struct type.
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct StructInfo {
     /// Concretized name of the struct type.
     pub name: String,
@@ -30,7 +31,7 @@ pub struct StructInfo {
 }
 
 /// Debug info of a Sierra enum type.
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct EnumInfo {
     /// Concretized name of the enum type.
     pub name: String,
@@ -39,32 +40,12 @@ pub struct EnumInfo {
 }
 
 /// An id of a sierra type - equivalent to `id` field of [`cairo_lang_sierra::ids::ConcreteTypeId`].
-#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct SierraTypeId(pub u64);
 
-// We can't use untagged enum here. See https://github.com/serde-rs/json/issues/1103
-impl Serialize for VersionedTypeNamesAnnotations {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        match self {
-            VersionedTypeNamesAnnotations::V1(v1) => v1.serialize(serializer),
-        }
-    }
-}
-
-impl<'de> Deserialize<'de> for VersionedTypeNamesAnnotations {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        TypeNamesAnnotationsV1::deserialize(deserializer).map(VersionedTypeNamesAnnotations::V1)
-    }
-}
-
-impl_namespace!(
+impl_versioned_namespace!(
     "github.com/software-mansion-labs/cairo-debugger/user-types",
+    VersionedTypeNamesAnnotations,
     TypeNamesAnnotationsV1,
-    VersionedTypeNamesAnnotations
+    "Type Names Annotations"
 );
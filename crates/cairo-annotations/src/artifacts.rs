@@ -0,0 +1,368 @@
+use crate::annotations::coverage::VersionedCoverageAnnotations;
+use crate::annotations::debugger::VersionedDebuggerAnnotations;
+use crate::annotations::profiler::VersionedProfilerAnnotations;
+use crate::annotations::type_names::VersionedTypeNamesAnnotations;
+use crate::annotations::{AnnotationsError, TryFromDebugInfo};
+use crate::contract_class::ContractEntryPoints;
+use crate::trace_data::CairoExecutionInfo;
+use cairo_lang_sierra::debug_info::DebugInfo;
+use cairo_lang_sierra::program::{Program, VersionedProgram};
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{fs, io};
+use thiserror::Error;
+
+/// Layout of a Sierra artifact file produced by Scarb.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ArtifactKind {
+    /// A `*.sierra.json` file with a [`VersionedProgram`], e.g. of an executable or a test target.
+    Program,
+    /// A `*.contract_class.json` file with the Sierra program of a contract encoded as felts
+    /// and its debug info in `sierra_program_debug_info`.
+    ContractClass,
+}
+
+/// Sierra debug info read from an artifact file.
+///
+/// Annotations are extracted from the debug info on demand with [`SierraArtifact::annotations`],
+/// so invalid annotations of one namespace do not prevent using the others.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SierraArtifact {
+    pub kind: ArtifactKind,
+    /// The Sierra program. Only available for [`ArtifactKind::Program`], contract classes store it
+    /// encoded as felts, which are not decoded by this crate.
+    pub program: Option<Program>,
+    pub debug_info: DebugInfo,
+    /// Entry points of the contract. Only available for [`ArtifactKind::ContractClass`].
+    pub entry_points: Option<ContractEntryPoints>,
+}
+
+/// Annotations of all namespaces found in Sierra debug info, each extracted independently.
+/// Annotations missing from the debug info are `Ok(None)`, e.g. if they were not enabled in the Scarb config.
+#[derive(Debug)]
+pub struct ArtifactAnnotations {
+    pub coverage: Result<Option<VersionedCoverageAnnotations>, AnnotationsError>,
+    pub profiler: Result<Option<VersionedProfilerAnnotations>, AnnotationsError>,
+    pub debugger: Result<Option<VersionedDebuggerAnnotations>, AnnotationsError>,
+    pub type_names: Result<Option<VersionedTypeNamesAnnotations>, AnnotationsError>,
+}
+
+/// Sierra artifact of a contract listed in a `*.starknet_artifacts.json` manifest.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContractArtifact {
+    pub package_name: String,
+    pub contract_name: String,
+    pub module_path: String,
+    /// Path to the `*.contract_class.json` file.
+    pub sierra_path: Utf8PathBuf,
+    /// Path to the `*.compiled_contract_class.json` file, present if Scarb was configured with `casm = true`.
+    /// Load it with [`load_casm_class`](crate::contract_class::load_casm_class).
+    pub casm_path: Option<Utf8PathBuf>,
+    pub artifact: SierraArtifact,
+}
+
+/// Enum representing the possible errors that can occur when loading a Sierra artifact.
+#[derive(Debug, Error)]
+pub enum ArtifactError {
+    /// Error indicating that the artifact file could not be read.
+    #[error("Failed to read artifact file {path}: {source}")]
+    Io {
+        path: Utf8PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    /// Error indicating that the artifact is not valid JSON or misses required fields.
+    #[error("Deserialization error: {0}")]
+    DeserializationError(#[from] serde_json::Error),
+
+    /// Error indicating that the artifact is neither a Sierra program nor a contract class.
+    #[error("Unknown artifact layout, expected a Sierra program or a contract class")]
+    UnknownLayout,
+
+    /// Error indicating that the artifact was compiled without debug info.
+    #[error("Missing debug info in artifact")]
+    MissingDebugInfo,
+}
+
+#[derive(Deserialize)]
+struct StarknetArtifacts {
+    contracts: Vec<StarknetContract>,
+}
+
+#[derive(Deserialize)]
+struct StarknetContract {
+    package_name: String,
+    contract_name: String,
+    module_path: String,
+    artifacts: StarknetContractArtifacts,
+}
+
+#[derive(Deserialize)]
+struct StarknetContractArtifacts {
+    sierra: Utf8PathBuf,
+    #[serde(default)]
+    casm: Option<Utf8PathBuf>,
+}
+
+impl ArtifactAnnotations {
+    /// Extracts annotations of all namespaces from `debug_info`.
+    #[must_use]
+    pub fn from_debug_info(debug_info: &DebugInfo) -> Self {
+        Self {
+            coverage: optional_annotations(debug_info),
+            profiler: optional_annotations(debug_info),
+            debugger: optional_annotations(debug_info),
+            type_names: optional_annotations(debug_info),
+        }
+    }
+}
+
+impl SierraArtifact {
+    /// Extracts annotations of a single namespace, e.g. `artifact.annotations::<VersionedCoverageAnnotations>()`.
+    ///
+    /// Returns `None` if the namespace is missing from the debug info.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the annotations of the namespace cannot be deserialized.
+    pub fn annotations<T>(&self) -> Result<Option<T>, AnnotationsError>
+    where
+        T: TryFromDebugInfo<Error = AnnotationsError>,
+    {
+        optional_annotations(&self.debug_info)
+    }
+
+    /// Extracts annotations of all namespaces. See [`ArtifactAnnotations::from_debug_info`].
+    #[must_use]
+    pub fn all_annotations(&self) -> ArtifactAnnotations {
+        ArtifactAnnotations::from_debug_info(&self.debug_info)
+    }
+}
+
+impl CairoExecutionInfo {
+    /// Loads the Sierra artifact the trace was executed from, see [`load_sierra_artifact`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the artifact cannot be loaded.
+    pub fn load_source_sierra(&self) -> Result<SierraArtifact, ArtifactError> {
+        load_sierra_artifact(&self.source_sierra_path)
+    }
+}
+
+/// Reads and loads a Sierra artifact. See [`parse_sierra_artifact`].
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or the artifact cannot be loaded.
+pub fn load_sierra_artifact(path: &Utf8Path) -> Result<SierraArtifact, ArtifactError> {
+    parse_sierra_artifact(&read(path)?)
+}
+
+/// Loads a Sierra artifact from JSON, detecting whether it is a `*.sierra.json` [`VersionedProgram`]
+/// or a `*.contract_class.json` contract class.
+///
+/// # Errors
+///
+/// Returns an error if the artifact has an unknown layout or has no debug info.
+pub fn parse_sierra_artifact(json: &str) -> Result<SierraArtifact, ArtifactError> {
+    let value: Value = serde_json::from_str(json)?;
+
+    let (kind, program, debug_info, entry_points) = if value.get("sierra_program").is_some() {
+        let debug_info = value
+            .get("sierra_program_debug_info")
+            .map(Option::<DebugInfo>::deserialize)
+            .transpose()?
+            .flatten();
+        let entry_points = value
+            .get("entry_points_by_type")
+            .map(ContractEntryPoints::deserialize)
+            .transpose()?;
+        (ArtifactKind::ContractClass, None, debug_info, entry_points)
+    } else if value.get("type_declarations").is_some() {
+        let program = VersionedProgram::deserialize(value)?
+            .into_v1()
+            .map_err(|_| ArtifactError::UnknownLayout)?;
+        (
+            ArtifactKind::Program,
+            Some(program.program),
+            program.debug_info,
+            None,
+        )
+    } else {
+        return Err(ArtifactError::UnknownLayout);
+    };
+
+    let debug_info = debug_info.ok_or(ArtifactError::MissingDebugInfo)?;
+    Ok(SierraArtifact {
+        kind,
+        program,
+        debug_info,
+        entry_points,
+    })
+}
+
+/// Loads Sierra artifacts of all contracts listed in a `*.starknet_artifacts.json` manifest
+/// generated by Scarb for the `starknet-contract` target.
+///
+/// # Errors
+///
+/// Returns an error if the manifest or any of the contract classes cannot be loaded.
+pub fn load_starknet_artifacts(
+    manifest_path: &Utf8Path,
+) -> Result<Vec<ContractArtifact>, ArtifactError> {
+    let manifest: StarknetArtifacts = serde_json::from_str(&read(manifest_path)?)?;
+    let artifacts_dir = manifest_path.parent().unwrap_or(Utf8Path::new(""));
+
+    manifest
+        .contracts
+        .into_iter()
+        .map(|contract| {
+            let sierra_path = artifacts_dir.join(&contract.artifacts.sierra);
+            Ok(ContractArtifact {
+                package_name: contract.package_name,
+                contract_name: contract.contract_name,
+                module_path: contract.module_path,
+                artifact: load_sierra_artifact(&sierra_path)?,
+                sierra_path,
+                casm_path: contract.artifacts.casm.map(|casm| artifacts_dir.join(casm)),
+            })
+        })
+        .collect()
+}
+
+fn optional_annotations<T>(debug_info: &DebugInfo) -> Result<Option<T>, AnnotationsError>
+where
+    T: TryFromDebugInfo<Error = AnnotationsError>,
+{
+    match T::try_from_debug_info(debug_info) {
+        Ok(annotations) => Ok(Some(annotations)),
+        Err(AnnotationsError::MissingNamespace(_)) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+fn read(path: &Utf8Path) -> Result<String, ArtifactError> {
+    fs::read_to_string(path).map_err(|source| ArtifactError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::annotations::profiler::ProfilerAnnotationsV1;
+    use cairo_lang_sierra::ProgramParser;
+    use serde_json::json;
+
+    fn debug_info() -> Value {
+        json!({
+            "type_names": [],
+            "libfunc_names": [],
+            "user_func_names": [],
+            "annotations": {
+                "github.com/software-mansion/cairo-profiler": {
+                    "statements_functions": { "0": ["pkg::main"] }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_parse_program() {
+        let program = ProgramParser::new()
+            .parse("return(); pkg::main@0() -> ();")
+            .unwrap();
+        let mut json = serde_json::to_value(VersionedProgram::v1(
+            cairo_lang_sierra::program::ProgramArtifact::stripped(program.clone()),
+        ))
+        .unwrap();
+        json["debug_info"] = debug_info();
+
+        let artifact = parse_sierra_artifact(&json.to_string()).unwrap();
+
+        assert_eq!(artifact.kind, ArtifactKind::Program);
+        assert_eq!(artifact.program, Some(program));
+        let Some(VersionedProfilerAnnotations::V1(ProfilerAnnotationsV1 {
+            statements_functions,
+        })) = artifact.annotations().unwrap()
+        else {
+            panic!("missing profiler annotations");
+        };
+        assert_eq!(statements_functions.len(), 1);
+        assert_eq!(
+            artifact
+                .annotations::<VersionedCoverageAnnotations>()
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_contract_class() {
+        let json = json!({
+            "sierra_program": ["0x1", "0x2"],
+            "sierra_program_debug_info": debug_info(),
+            "contract_class_version": "0.1.0",
+            "entry_points_by_type": {
+                "EXTERNAL": [{ "selector": "0x1", "function_idx": 0 }],
+                "L1_HANDLER": [],
+                "CONSTRUCTOR": [],
+            },
+            "abi": [],
+        });
+
+        let artifact = parse_sierra_artifact(&json.to_string()).unwrap();
+
+        assert_eq!(artifact.kind, ArtifactKind::ContractClass);
+        assert_eq!(artifact.program, None);
+        assert!(
+            artifact
+                .annotations::<VersionedProfilerAnnotations>()
+                .unwrap()
+                .is_some()
+        );
+        assert_eq!(artifact.entry_points.unwrap().external.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            parse_sierra_artifact(r#"{"sierra_program": [], "sierra_program_debug_info": null}"#),
+            Err(ArtifactError::MissingDebugInfo)
+        ));
+        assert!(matches!(
+            parse_sierra_artifact(r#"{"sierra_program": []}"#),
+            Err(ArtifactError::MissingDebugInfo)
+        ));
+        assert!(matches!(
+            parse_sierra_artifact(r#"{"contracts": []}"#),
+            Err(ArtifactError::UnknownLayout)
+        ));
+    }
+
+    #[test]
+    fn test_invalid_annotations() {
+        let mut debug_info = debug_info();
+        debug_info["annotations"]["github.com/software-mansion/cairo-coverage"] =
+            json!({ "version": 2 });
+        let json = json!({
+            "sierra_program": [],
+            "sierra_program_debug_info": debug_info,
+        });
+
+        let artifact = parse_sierra_artifact(&json.to_string()).unwrap();
+
+        assert!(matches!(
+            artifact.annotations::<VersionedCoverageAnnotations>(),
+            Err(AnnotationsError::UnsupportedVersion { version: 2, .. })
+        ));
+        let annotations = artifact.all_annotations();
+        assert!(annotations.coverage.is_err());
+        assert!(annotations.profiler.unwrap().is_some());
+        assert!(annotations.debugger.unwrap().is_none());
+    }
+}
@@ -0,0 +1,624 @@
+use crate::map_pcs_to_sierra_statement_ids::{MappingResult, map_pc_to_sierra_statement_id};
+use crate::trace_data::{CallTraceV1, EntryPointSelector, EntryPointType};
+use cairo_lang_sierra::extensions::gas::CostTokenType;
+use cairo_lang_sierra::program::{Function, Program};
+use cairo_lang_sierra_to_casm::compiler::{
+    CairoProgram, CairoProgramDebugInfo, CompilationError, SierraToCasmConfig, compile,
+};
+use cairo_lang_sierra_to_casm::metadata::{
+    MetadataComputationConfig, MetadataError, calc_metadata,
+};
+use cairo_lang_sierra_type_size::ProgramRegistryInfo;
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::Deserialize;
+use starknet_types_core::felt::Felt;
+use std::{fs, io};
+use thiserror::Error;
+
+/// Gas cost enforced on entry point functions, the same as in `CasmContractClass::from_contract_class` of
+/// `cairo-lang-starknet-classes`.
+const ENTRY_POINT_COST: i32 = 10000;
+
+/// Entry points of a contract class, as in `entry_points_by_type` of `*.contract_class.json`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+pub struct ContractEntryPoints {
+    #[serde(rename = "EXTERNAL")]
+    pub external: Vec<ContractEntryPoint>,
+    #[serde(rename = "L1_HANDLER")]
+    pub l1_handler: Vec<ContractEntryPoint>,
+    #[serde(rename = "CONSTRUCTOR")]
+    pub constructor: Vec<ContractEntryPoint>,
+}
+
+/// An entry point of a contract class, pointing to a function of its Sierra program.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+pub struct ContractEntryPoint {
+    pub selector: EntryPointSelector,
+    /// Index of the function in the Sierra program of the contract.
+    pub function_idx: usize,
+}
+
+/// A `*.compiled_contract_class.json` file generated by Scarb with `casm = true`.
+///
+/// Only the fields needed to map traces are read, use `CasmContractClass` of `cairo-lang-starknet-classes` for
+/// the full compiled contract class.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+pub struct CompiledContractClassFile {
+    pub bytecode: Vec<Felt>,
+    pub entry_points_by_type: CasmEntryPoints,
+}
+
+/// Entry points of a compiled contract class, as in `entry_points_by_type` of `*.compiled_contract_class.json`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+pub struct CasmEntryPoints {
+    #[serde(rename = "EXTERNAL")]
+    pub external: Vec<CasmEntryPoint>,
+    #[serde(rename = "L1_HANDLER")]
+    pub l1_handler: Vec<CasmEntryPoint>,
+    #[serde(rename = "CONSTRUCTOR")]
+    pub constructor: Vec<CasmEntryPoint>,
+}
+
+/// An entry point of a compiled contract class, pointing to an offset in its bytecode.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+pub struct CasmEntryPoint {
+    pub selector: EntryPointSelector,
+    pub offset: usize,
+    #[serde(default)]
+    pub builtins: Vec<String>,
+}
+
+/// An entry point of a contract class together with its location in the compiled CASM.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CompiledEntryPoint {
+    pub entry_point_type: EntryPointType,
+    pub selector: EntryPointSelector,
+    pub function_idx: usize,
+    /// Offset of the first CASM instruction of the entry point function in the bytecode,
+    /// the same as the `offset` of the entry point in `CompiledContractClassFile`.
+    pub offset: usize,
+}
+
+/// The Sierra program of a contract class compiled to CASM the same way as when declaring it on Starknet.
+#[derive(Debug, Eq, PartialEq)]
+pub struct CompiledContractClass {
+    pub debug_info: CairoProgramDebugInfo,
+    pub entry_points: Vec<CompiledEntryPoint>,
+}
+
+/// Enum representing the possible errors that can occur when compiling a contract class or mapping its traces.
+#[derive(Debug, Error)]
+pub enum ContractClassError {
+    /// Error indicating that the compiled contract class file could not be read.
+    #[error("Failed to read compiled contract class file {path}: {source}")]
+    Io {
+        path: Utf8PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    /// Error indicating that the compiled contract class is not valid JSON or misses required fields.
+    #[error("Deserialization error: {0}")]
+    DeserializationError(#[from] serde_json::Error),
+
+    /// Error indicating that an entry point points to a function missing from the Sierra program.
+    #[error("Entry point function index out of range: {0}")]
+    FunctionIdxOutOfRange(usize),
+
+    /// Error indicating that the Sierra program could not be compiled to CASM.
+    #[error("Compilation error: {0}")]
+    CompilationError(String),
+
+    /// Error indicating that the Sierra program compiles to a different bytecode than the compiled contract class,
+    /// e.g. because the contract class was compiled with a different version of the compiler.
+    #[error("Compiled bytecode does not match the compiled contract class")]
+    BytecodeMismatch,
+
+    /// Error indicating that the contract class has no entry point called in the trace.
+    #[error("Entry point not found: {entry_point_type:?} {selector:#x}")]
+    EntryPointNotFound {
+        entry_point_type: EntryPointType,
+        selector: starknet_types_core::felt::Felt,
+    },
+
+    /// Error indicating that the trace has no CASM level information.
+    #[error("Missing Cairo execution info in call trace")]
+    MissingExecutionInfo,
+
+    /// Error indicating that the trace does not start at the entry point called.
+    #[error("Trace does not start at the entry point at offset {0}")]
+    TraceStartMismatch(usize),
+}
+
+impl CasmEntryPoints {
+    /// Iterates over all entry points along with their type.
+    pub fn iter(&self) -> impl Iterator<Item = (EntryPointType, &CasmEntryPoint)> {
+        [
+            (EntryPointType::External, &self.external),
+            (EntryPointType::L1Handler, &self.l1_handler),
+            (EntryPointType::Constructor, &self.constructor),
+        ]
+        .into_iter()
+        .flat_map(|(entry_point_type, entry_points)| {
+            entry_points
+                .iter()
+                .map(move |entry_point| (entry_point_type.clone(), entry_point))
+        })
+    }
+}
+
+impl ContractEntryPoints {
+    /// Iterates over all entry points along with their type.
+    pub fn iter(&self) -> impl Iterator<Item = (EntryPointType, &ContractEntryPoint)> {
+        [
+            (EntryPointType::External, &self.external),
+            (EntryPointType::L1Handler, &self.l1_handler),
+            (EntryPointType::Constructor, &self.constructor),
+        ]
+        .into_iter()
+        .flat_map(|(entry_point_type, entry_points)| {
+            entry_points
+                .iter()
+                .map(move |entry_point| (entry_point_type.clone(), entry_point))
+        })
+    }
+}
+
+impl CompiledContractClass {
+    /// Compiles the Sierra program of a contract class to CASM, with the same metadata as used by
+    /// `CasmContractClass::from_contract_class` of `cairo-lang-starknet-classes`, so that the pcs of traces
+    /// of its calls match the compiled instructions.
+    ///
+    /// Prefer [`CompiledContractClass::from_casm_class`] if the compiled contract class is available,
+    /// as it verifies that the compilation matches the class declared on Starknet.
+    ///
+    /// The Sierra program of a contract class is stored encoded as felts, which this crate does not decode.
+    /// Decode it with `ContractClass::extract_sierra_program` of `cairo-lang-starknet-classes`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an entry point points to a function missing from the program
+    /// or the program cannot be compiled.
+    pub fn compile(
+        program: &Program,
+        entry_points: &ContractEntryPoints,
+    ) -> Result<Self, ContractClassError> {
+        let functions = entry_point_functions(program, entry_points)?;
+        let casm = compile_contract_program(program, &functions)?;
+
+        let entry_points = entry_points
+            .iter()
+            .zip(functions)
+            .map(
+                |((entry_point_type, entry_point), function)| CompiledEntryPoint {
+                    entry_point_type,
+                    selector: entry_point.selector.clone(),
+                    function_idx: entry_point.function_idx,
+                    offset: casm.debug_info.sierra_statement_info[function.entry_point.0]
+                        .start_offset,
+                },
+            )
+            .collect();
+
+        Ok(Self {
+            debug_info: casm.debug_info,
+            entry_points,
+        })
+    }
+
+    /// Compiles the Sierra program of a contract class to CASM for the debug info, and takes the offsets of entry
+    /// points from `casm_class` - the compiled contract class loaded with [`load_casm_class`]. `program` is
+    /// the decoded Sierra program of the contract class, see [`CompiledContractClass::compile`].
+    ///
+    /// The compiled bytecode is compared with the one of `casm_class`, so the debug info is guaranteed to match
+    /// the instructions run on Starknet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an entry point points to a function missing from the program or is missing from
+    /// `casm_class`, the program cannot be compiled, or it compiles to a different bytecode.
+    pub fn from_casm_class(
+        program: &Program,
+        entry_points: &ContractEntryPoints,
+        casm_class: &CompiledContractClassFile,
+    ) -> Result<Self, ContractClassError> {
+        let functions = entry_point_functions(program, entry_points)?;
+        let casm = compile_contract_program(program, &functions)?;
+
+        let bytecode = casm.assemble().bytecode;
+        if bytecode.len() != casm_class.bytecode.len()
+            || bytecode
+                .iter()
+                .zip(&casm_class.bytecode)
+                .any(|(compiled, expected)| Felt::from(compiled) != *expected)
+        {
+            return Err(ContractClassError::BytecodeMismatch);
+        }
+
+        let entry_points = entry_points
+            .iter()
+            .map(|(entry_point_type, entry_point)| {
+                let casm_entry_point = casm_class
+                    .entry_points_by_type
+                    .iter()
+                    .find(|(casm_entry_point_type, casm_entry_point)| {
+                        *casm_entry_point_type == entry_point_type
+                            && casm_entry_point.selector == entry_point.selector
+                    })
+                    .map(|(_, casm_entry_point)| casm_entry_point)
+                    .ok_or_else(|| ContractClassError::EntryPointNotFound {
+                        entry_point_type: entry_point_type.clone(),
+                        selector: entry_point.selector.0,
+                    })?;
+                Ok(CompiledEntryPoint {
+                    entry_point_type,
+                    selector: entry_point.selector.clone(),
+                    function_idx: entry_point.function_idx,
+                    offset: casm_entry_point.offset,
+                })
+            })
+            .collect::<Result<_, ContractClassError>>()?;
+
+        Ok(Self {
+            debug_info: casm.debug_info,
+            entry_points,
+        })
+    }
+
+    /// Finds the entry point of the given type and selector.
+    #[must_use]
+    pub fn entry_point(
+        &self,
+        entry_point_type: &EntryPointType,
+        selector: &EntryPointSelector,
+    ) -> Option<&CompiledEntryPoint> {
+        self.entry_points.iter().find(|entry_point| {
+            &entry_point.entry_point_type == entry_point_type && &entry_point.selector == selector
+        })
+    }
+
+    /// Maps the pcs of a trace of a call to this contract class to the sierra statement ids.
+    ///
+    /// The called entry point is selected by the type and selector of the entry point of `call_trace`,
+    /// and the program offset is computed from the first pc of the trace, which is the first
+    /// instruction of the entry point function, so no assumptions about headers are needed.
+    /// Nested calls are not mapped, they must be mapped with the contract classes they called.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the trace has no CASM level information, the entry point is not found
+    /// or the trace does not start at it.
+    pub fn map_call_trace(
+        &self,
+        call_trace: &CallTraceV1,
+    ) -> Result<Vec<MappingResult>, ContractClassError> {
+        let call_entry_point = &call_trace.entry_point;
+        let entry_point = self
+            .entry_point(
+                &call_entry_point.entry_point_type,
+                &call_entry_point.entry_point_selector,
+            )
+            .ok_or_else(|| ContractClassError::EntryPointNotFound {
+                entry_point_type: call_entry_point.entry_point_type.clone(),
+                selector: call_entry_point.entry_point_selector.0,
+            })?;
+        let vm_trace = &call_trace
+            .cairo_execution_info
+            .as_ref()
+            .ok_or(ContractClassError::MissingExecutionInfo)?
+            .casm_level_info
+            .vm_trace;
+
+        let Some(first_pc) = vm_trace.first().map(|trace_entry| trace_entry.pc) else {
+            return Ok(Vec::new());
+        };
+        if self.debug_info.sierra_statement_info.is_empty() {
+            return Ok(Vec::new());
+        }
+        let real_minimal_pc = first_pc
+            .checked_sub(entry_point.offset)
+            .ok_or(ContractClassError::TraceStartMismatch(entry_point.offset))?;
+
+        Ok(vm_trace
+            .iter()
+            .map(|trace_entry| {
+                map_pc_to_sierra_statement_id(
+                    &self.debug_info.sierra_statement_info,
+                    trace_entry.pc,
+                    real_minimal_pc,
+                )
+            })
+            .collect())
+    }
+}
+
+/// Reads and parses a `*.compiled_contract_class.json` file.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or is not a compiled contract class.
+pub fn load_casm_class(path: &Utf8Path) -> Result<CompiledContractClassFile, ContractClassError> {
+    let content = fs::read_to_string(path).map_err(|source| ContractClassError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+fn entry_point_functions<'a>(
+    program: &'a Program,
+    entry_points: &ContractEntryPoints,
+) -> Result<Vec<&'a Function>, ContractClassError> {
+    entry_points
+        .iter()
+        .map(|(_, entry_point)| {
+            program.funcs.get(entry_point.function_idx).ok_or(
+                ContractClassError::FunctionIdxOutOfRange(entry_point.function_idx),
+            )
+        })
+        .collect()
+}
+
+/// Compiles the program with the entry point functions charged [`ENTRY_POINT_COST`], like
+/// `CasmContractClass::from_contract_class` does.
+fn compile_contract_program(
+    program: &Program,
+    entry_point_functions: &[&Function],
+) -> Result<CairoProgram, ContractClassError> {
+    let program_registry_info = ProgramRegistryInfo::new(program)
+        .map_err(|error| ContractClassError::CompilationError(error.to_string()))?;
+    let metadata = calc_metadata(
+        program,
+        &program_registry_info,
+        MetadataComputationConfig {
+            function_set_costs: entry_point_functions
+                .iter()
+                .map(|function| {
+                    (
+                        function.id.clone(),
+                        [(CostTokenType::Const, ENTRY_POINT_COST)]
+                            .into_iter()
+                            .collect(),
+                    )
+                })
+                .collect(),
+            ..MetadataComputationConfig::default()
+        },
+    )
+    .map_err(|error: MetadataError| ContractClassError::CompilationError(error.to_string()))?;
+    compile(
+        program,
+        &program_registry_info,
+        &metadata,
+        SierraToCasmConfig {
+            gas_usage_check: true,
+            max_bytecode_size: usize::MAX,
+        },
+    )
+    .map_err(|error: Box<CompilationError>| ContractClassError::CompilationError(error.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cairo_lang_sierra::ProgramParser;
+    use cairo_lang_sierra::program::StatementIdx;
+    use serde_json::json;
+
+    /// Two functions returning their argument if there is enough gas: `pkg::first` (statements 0-10)
+    /// and `pkg::second` (statements 11-21).
+    const PROGRAM: &str = "
+        type RangeCheck = RangeCheck;
+        type GasBuiltin = GasBuiltin;
+        type felt252 = felt252;
+
+        libfunc withdraw_gas = withdraw_gas;
+        libfunc branch_align = branch_align;
+        libfunc store_temp<RangeCheck> = store_temp<RangeCheck>;
+        libfunc store_temp<GasBuiltin> = store_temp<GasBuiltin>;
+        libfunc store_temp<felt252> = store_temp<felt252>;
+
+        withdraw_gas(range_check, gas) { fallthrough(range_check, gas) 6(range_check, gas) };
+        branch_align() -> ();
+        store_temp<RangeCheck>(range_check) -> (range_check);
+        store_temp<GasBuiltin>(gas) -> (gas);
+        store_temp<felt252>(value) -> (value);
+        return(range_check, gas, value);
+        branch_align() -> ();
+        store_temp<RangeCheck>(range_check) -> (range_check);
+        store_temp<GasBuiltin>(gas) -> (gas);
+        store_temp<felt252>(value) -> (value);
+        return(range_check, gas, value);
+        withdraw_gas(range_check, gas) { fallthrough(range_check, gas) 17(range_check, gas) };
+        branch_align() -> ();
+        store_temp<RangeCheck>(range_check) -> (range_check);
+        store_temp<GasBuiltin>(gas) -> (gas);
+        store_temp<felt252>(value) -> (value);
+        return(range_check, gas, value);
+        branch_align() -> ();
+        store_temp<RangeCheck>(range_check) -> (range_check);
+        store_temp<GasBuiltin>(gas) -> (gas);
+        store_temp<felt252>(value) -> (value);
+        return(range_check, gas, value);
+
+        pkg::first@0(range_check: RangeCheck, gas: GasBuiltin, value: felt252) -> (RangeCheck, GasBuiltin, felt252);
+        pkg::second@11(range_check: RangeCheck, gas: GasBuiltin, value: felt252) -> (RangeCheck, GasBuiltin, felt252);
+    ";
+
+    fn entry_points() -> ContractEntryPoints {
+        serde_json::from_value(json!({
+            "EXTERNAL": [
+                { "selector": "0x1", "function_idx": 0 },
+                { "selector": "0x2", "function_idx": 1 },
+            ],
+            "L1_HANDLER": [],
+            "CONSTRUCTOR": [],
+        }))
+        .unwrap()
+    }
+
+    fn compiled_contract_class() -> CompiledContractClass {
+        let program = ProgramParser::new().parse(PROGRAM).unwrap();
+        CompiledContractClass::compile(&program, &entry_points()).unwrap()
+    }
+
+    /// `*.compiled_contract_class.json` of the program, with the bytecode and entry point offsets of `compiled`.
+    fn casm_class_json(compiled: &CompiledContractClass) -> serde_json::Value {
+        let program = ProgramParser::new().parse(PROGRAM).unwrap();
+        let functions = entry_point_functions(&program, &entry_points()).unwrap();
+        let bytecode: Vec<_> = compile_contract_program(&program, &functions)
+            .unwrap()
+            .assemble()
+            .bytecode
+            .iter()
+            .map(|value| format!("{:#x}", Felt::from(value)))
+            .collect();
+        let external: Vec<_> = compiled
+            .entry_points
+            .iter()
+            .map(|entry_point| {
+                json!({
+                    "selector": format!("{:#x}", entry_point.selector.0),
+                    "offset": entry_point.offset,
+                    "builtins": ["range_check"],
+                })
+            })
+            .collect();
+        json!({
+            "prime": "0x800000000000011000000000000000000000000000000000000000000000001",
+            "compiler_version": "2.17.0",
+            "bytecode": bytecode,
+            "bytecode_segment_lengths": [],
+            "hints": [],
+            "entry_points_by_type": {
+                "EXTERNAL": external,
+                "L1_HANDLER": [],
+                "CONSTRUCTOR": [],
+            },
+        })
+    }
+
+    fn call_trace(selector: &str, pcs: &[usize]) -> CallTraceV1 {
+        let vm_trace: Vec<_> = pcs
+            .iter()
+            .map(|pc| json!({ "pc": pc, "ap": 10, "fp": 10 }))
+            .collect();
+        serde_json::from_value(json!({
+            "entry_point": {
+                "class_hash": null,
+                "entry_point_type": "EXTERNAL",
+                "entry_point_selector": selector,
+                "contract_address": "0x123",
+                "call_type": "Call",
+                "contract_name": null,
+                "function_name": null,
+            },
+            "used_execution_resources": {
+                "vm_resources": { "n_steps": 1, "n_memory_holes": 0, "builtin_instance_counter": {} },
+                "gas_consumed": null,
+            },
+            "used_l1_resources": { "l2_l1_message_sizes": [] },
+            "nested_calls": [],
+            "cairo_execution_info": {
+                "source_sierra_path": "pkg_Contract.contract_class.json",
+                "casm_level_info": { "run_with_call_header": false, "vm_trace": vm_trace, "program_offset": null },
+                "enable_gas": null,
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_compile() {
+        let compiled = compiled_contract_class();
+
+        let offset =
+            |statement: usize| compiled.debug_info.sierra_statement_info[statement].start_offset;
+        let entry_point = compiled
+            .entry_point(&EntryPointType::External, &EntryPointSelector(2_u8.into()))
+            .unwrap();
+        assert_eq!(entry_point.function_idx, 1);
+        assert_eq!(entry_point.offset, offset(11));
+        assert!(entry_point.offset > 0);
+        assert_eq!(
+            compiled.entry_point(&EntryPointType::L1Handler, &EntryPointSelector(2_u8.into())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_map_call_trace() {
+        let compiled = compiled_contract_class();
+        let offset = compiled
+            .entry_point(&EntryPointType::External, &EntryPointSelector(2_u8.into()))
+            .unwrap()
+            .offset;
+
+        // The contract is loaded at pc 5 instead of 1.
+        let mapping_results = compiled
+            .map_call_trace(&call_trace("0x2", &[5 + offset, 4]))
+            .unwrap();
+
+        assert_eq!(
+            mapping_results,
+            vec![
+                MappingResult::SierraStatementIdx(StatementIdx(11)),
+                MappingResult::Header
+            ]
+        );
+    }
+
+    #[test]
+    fn test_map_call_trace_errors() {
+        let compiled = compiled_contract_class();
+
+        assert!(matches!(
+            compiled.map_call_trace(&call_trace("0x3", &[1])),
+            Err(ContractClassError::EntryPointNotFound { .. })
+        ));
+        assert!(matches!(
+            compiled.map_call_trace(&call_trace("0x2", &[1])),
+            Err(ContractClassError::TraceStartMismatch(_))
+        ));
+        assert!(matches!(
+            CompiledContractClass::compile(
+                &ProgramParser::new().parse(PROGRAM).unwrap(),
+                &ContractEntryPoints {
+                    constructor: vec![ContractEntryPoint {
+                        selector: EntryPointSelector::default(),
+                        function_idx: 2,
+                    }],
+                    ..ContractEntryPoints::default()
+                }
+            ),
+            Err(ContractClassError::FunctionIdxOutOfRange(2))
+        ));
+    }
+
+    #[test]
+    fn test_from_casm_class_errors() {
+        let program = ProgramParser::new().parse(PROGRAM).unwrap();
+        let compiled = compiled_contract_class();
+        let casm_class: CompiledContractClassFile =
+            serde_json::from_value(casm_class_json(&compiled)).unwrap();
+
+        let mut other_bytecode = casm_class.clone();
+        other_bytecode.bytecode[0] += Felt::ONE;
+        assert!(matches!(
+            CompiledContractClass::from_casm_class(&program, &entry_points(), &other_bytecode),
+            Err(ContractClassError::BytecodeMismatch)
+        ));
+
+        let mut missing_entry_point = casm_class;
+        missing_entry_point.entry_points_by_type.external.pop();
+        assert!(matches!(
+            CompiledContractClass::from_casm_class(&program, &entry_points(), &missing_entry_point),
+            Err(ContractClassError::EntryPointNotFound { .. })
+        ));
+
+        assert!(matches!(
+            load_casm_class(Utf8Path::new("missing.compiled_contract_class.json")),
+            Err(ContractClassError::Io { .. })
+        ));
+    }
+}
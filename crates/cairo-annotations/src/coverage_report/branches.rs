@@ -0,0 +1,204 @@
+use crate::MappingResult;
+use crate::statement_executions;
+use cairo_lang_sierra::program::{BranchTarget, Program, Statement, StatementIdx};
+use std::collections::BTreeMap;
+
+/// Branch coverage of sierra statements which have more than one branch target, e.g. `enum_match`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BranchCoverage {
+    pub statements: BTreeMap<StatementIdx, StatementBranches>,
+}
+
+/// Branches of a single sierra statement and how many times each of them was taken.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StatementBranches {
+    /// Statements the branches continue to, in the order of the libfunc branches.
+    pub targets: Vec<StatementIdx>,
+    /// Number of times the statement was executed.
+    pub executions: usize,
+    /// Number of times each branch was taken, indexed the same as `targets`.
+    pub taken: Vec<usize>,
+}
+
+impl BranchCoverage {
+    /// Finds all branching statements in the program. None of the branches is taken yet.
+    #[must_use]
+    pub fn new(program: &Program) -> Self {
+        let statements = program
+            .statements
+            .iter()
+            .enumerate()
+            .filter_map(|(index, statement)| {
+                let Statement::Invocation(invocation) = statement else {
+                    return None;
+                };
+                let statement_idx = StatementIdx(index);
+                let targets: Vec<_> = invocation
+                    .branches
+                    .iter()
+                    .map(|branch| statement_idx.next(branch.target))
+                    .collect();
+
+                (targets.len() > 1).then(|| {
+                    let taken = vec![0; targets.len()];
+                    (
+                        statement_idx,
+                        StatementBranches {
+                            targets,
+                            executions: 0,
+                            taken,
+                        },
+                    )
+                })
+            })
+            .collect();
+
+        Self { statements }
+    }
+
+    /// Records branches taken in a trace mapped with
+    /// [`map_pcs_to_sierra_statement_ids`](crate::map_pcs_to_sierra_statement_ids).
+    ///
+    /// `program` must be the same program this coverage was created for.
+    pub fn record_trace(&mut self, program: &Program, mapping_results: &[MappingResult]) {
+        let executions: Vec<_> = statement_executions(mapping_results).collect();
+
+        for (index, statement_idx) in executions.iter().enumerate() {
+            let Some(branches) = self.statements.get_mut(statement_idx) else {
+                continue;
+            };
+            branches.executions += 1;
+
+            let Some(&next_statement_idx) = executions.get(index + 1) else {
+                continue;
+            };
+            if let Some(branch) = taken_branch(program, &branches.targets, next_statement_idx) {
+                branches.taken[branch] += 1;
+            }
+        }
+    }
+}
+
+impl StatementBranches {
+    /// Number of branches taken at least once.
+    #[must_use]
+    pub fn branches_hit(&self) -> usize {
+        self.taken.iter().filter(|&&taken| taken > 0).count()
+    }
+}
+
+/// Finds the branch that leads to `next_statement_idx`.
+///
+/// Statements like `branch_align` often compile to no CASM instructions and never appear in the trace,
+/// so the next executed statement may be a few statements after the branch target.
+/// Such statements always continue to the next statement, which lets us walk from the target
+/// to the executed statement.
+pub(crate) fn taken_branch(
+    program: &Program,
+    targets: &[StatementIdx],
+    next_statement_idx: StatementIdx,
+) -> Option<usize> {
+    let (branch, target) = targets
+        .iter()
+        .enumerate()
+        .filter(|(_, target)| target.0 <= next_statement_idx.0)
+        .max_by_key(|(_, target)| target.0)?;
+
+    let skipped_statements = program.statements.get(target.0..next_statement_idx.0)?;
+    let only_fallthrough = skipped_statements.iter().all(|statement| match statement {
+        Statement::Invocation(invocation) => {
+            matches!(
+                invocation.branches.as_slice(),
+                [branch] if branch.target == BranchTarget::Fallthrough
+            )
+        }
+        Statement::Return(_) => false,
+    });
+
+    only_fallthrough.then_some(branch)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cairo_lang_sierra::ids::ConcreteLibfuncId;
+    use cairo_lang_sierra::program::{BranchInfo, Invocation};
+
+    fn invocation(targets: &[BranchTarget]) -> Statement {
+        Statement::Invocation(Invocation {
+            libfunc_id: ConcreteLibfuncId::new(0),
+            args: vec![],
+            branches: targets
+                .iter()
+                .map(|&target| BranchInfo {
+                    target,
+                    results: vec![],
+                })
+                .collect(),
+        })
+    }
+
+    /// 0: `enum_match` - to 1 or 3
+    /// 1: `branch_align`
+    /// 2: return
+    /// 3: `branch_align`
+    /// 4: `store_temp`
+    /// 5: return
+    fn program() -> Program {
+        Program {
+            type_declarations: vec![],
+            libfunc_declarations: vec![],
+            statements: vec![
+                invocation(&[
+                    BranchTarget::Fallthrough,
+                    BranchTarget::Statement(StatementIdx(3)),
+                ]),
+                invocation(&[BranchTarget::Fallthrough]),
+                Statement::Return(vec![]),
+                invocation(&[BranchTarget::Fallthrough]),
+                invocation(&[BranchTarget::Fallthrough]),
+                Statement::Return(vec![]),
+            ],
+            funcs: vec![],
+        }
+    }
+
+    fn trace(statements: &[usize]) -> Vec<MappingResult> {
+        statements
+            .iter()
+            .map(|&statement| MappingResult::SierraStatementIdx(StatementIdx(statement)))
+            .collect()
+    }
+
+    #[test]
+    fn test_branching_statements() {
+        let branch_coverage = BranchCoverage::new(&program());
+        assert_eq!(
+            branch_coverage.statements.keys().collect::<Vec<_>>(),
+            vec![&StatementIdx(0)]
+        );
+        assert_eq!(
+            branch_coverage.statements[&StatementIdx(0)].targets,
+            vec![StatementIdx(1), StatementIdx(3)]
+        );
+    }
+
+    #[test]
+    fn test_record_trace() {
+        let program = program();
+        let mut branch_coverage = BranchCoverage::new(&program);
+
+        // Statement 0 spans two instructions, `branch_align` statements have no instructions.
+        branch_coverage.record_trace(&program, &trace(&[0, 0, 4, 5]));
+        branch_coverage.record_trace(&program, &trace(&[0, 4, 5]));
+
+        let branches = &branch_coverage.statements[&StatementIdx(0)];
+        assert_eq!(branches.executions, 2);
+        assert_eq!(branches.taken, vec![0, 2]);
+        assert_eq!(branches.branches_hit(), 1);
+
+        branch_coverage.record_trace(&program, &trace(&[0, 2]));
+        let branches = &branch_coverage.statements[&StatementIdx(0)];
+        assert_eq!(branches.taken, vec![1, 2]);
+    }
+}
@@ -0,0 +1,392 @@
+use crate::annotations::coverage::{CodeLocation, CoverageAnnotationsV1, SourceFileFullPath};
+use crate::annotations::profiler::{FunctionName, ParsedFunctionName, ProfilerAnnotationsV1};
+use crate::coverage_report::CoverageReport;
+use crate::source_code::{scarb_cache_relative_path, scarb_cache_relative_path_in};
+use camino::Utf8PathBuf;
+
+/// Names of modules whose functions are considered tests, like `#[cfg(test)] mod tests`.
+const TEST_MODULE_NAMES: [&str; 2] = ["tests", "test"];
+
+/// Configuration of which code is taken into account when computing coverage.
+///
+/// Glob patterns are matched against full paths of source files with virtual file markings removed,
+/// so to match files in a directory anywhere in the tree, prefix the pattern with `**/`.
+/// `*` matches any characters except `/`, `**` matches any characters including `/`
+/// and `?` matches a single character except `/`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CoverageFilter {
+    /// If not empty, only files matching at least one of these glob patterns are included.
+    pub include: Vec<String>,
+    /// Files matching any of these glob patterns are excluded, even if they match `include`.
+    pub exclude: Vec<String>,
+    /// Exclude code locations generated by macros, i.e. the ones with the macro flag set to `Some(true)`.
+    pub exclude_macro_generated: bool,
+    /// Exclude statements and functions from test modules, like `my_package::tests::test_sum`.
+    /// Statements can only be excluded if profiler annotations are provided.
+    pub exclude_tests: bool,
+    /// Exclude files of dependencies from the Scarb package cache, including the core library
+    /// and packages from registries and git.
+    pub exclude_dependencies: bool,
+    /// Scarb cache directory of the machine that compiled the program, e.g. the value of `SCARB_CACHE`.
+    /// If not set, dependencies are recognized by the default cache directory paths.
+    pub scarb_cache_dir: Option<Utf8PathBuf>,
+}
+
+impl CoverageFilter {
+    /// Returns whether the file at `path` should be included in the coverage.
+    #[must_use]
+    pub fn includes_path(&self, path: &SourceFileFullPath) -> bool {
+        let (real_path, _) = path.remove_virtual_file_markings();
+        let real_path = real_path.replace('\\', "/");
+
+        let included = self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|pattern| glob_matches(pattern, &real_path));
+        let excluded = self
+            .exclude
+            .iter()
+            .any(|pattern| glob_matches(pattern, &real_path));
+        let is_dependency = self.exclude_dependencies
+            && match &self.scarb_cache_dir {
+                Some(cache_dir) => scarb_cache_relative_path_in(&real_path, cache_dir).is_some(),
+                None => scarb_cache_relative_path(&real_path).is_some(),
+            };
+
+        included && !excluded && !is_dependency
+    }
+
+    /// Returns whether the code location should be included in the coverage.
+    #[must_use]
+    pub fn includes_location(&self, CodeLocation(path, _, macro_flag): &CodeLocation) -> bool {
+        let is_macro_generated = self.exclude_macro_generated && *macro_flag == Some(true);
+        !is_macro_generated && self.includes_path(path)
+    }
+
+    /// Returns whether the function should be included in the coverage.
+    #[must_use]
+    pub fn includes_function(&self, function_name: &str) -> bool {
+        !(self.exclude_tests && is_test_function(function_name))
+    }
+
+    /// Returns annotations with excluded code locations removed.
+    /// Statements left without any code location are removed entirely.
+    ///
+    /// `profiler_annotations` are needed to exclude statements generated from test functions.
+    #[must_use]
+    pub fn filter_annotations(
+        &self,
+        annotations: &CoverageAnnotationsV1,
+        profiler_annotations: Option<&ProfilerAnnotationsV1>,
+    ) -> CoverageAnnotationsV1 {
+        let statements_code_locations = annotations
+            .statements_code_locations
+            .iter()
+            .filter(|(statement_idx, _)| {
+                profiler_annotations
+                    .and_then(|profiler_annotations| {
+                        profiler_annotations.statements_functions.get(statement_idx)
+                    })
+                    .is_none_or(|function_names| {
+                        function_names
+                            .iter()
+                            .all(|FunctionName(name)| self.includes_function(name))
+                    })
+            })
+            .map(|(statement_idx, code_locations)| {
+                let code_locations: Vec<_> = code_locations
+                    .iter()
+                    .filter(|code_location| self.includes_location(code_location))
+                    .cloned()
+                    .collect();
+                (*statement_idx, code_locations)
+            })
+            .filter(|(_, code_locations)| !code_locations.is_empty())
+            .collect();
+
+        CoverageAnnotationsV1 {
+            statements_code_locations,
+        }
+    }
+
+    /// Removes excluded files and functions from the report.
+    ///
+    /// Useful for data not coming from coverage annotations, like functions added with
+    /// [`CoverageReport::add_functions`].
+    pub fn filter_report(&self, report: &mut CoverageReport) {
+        report
+            .files
+            .retain(|path, _| self.includes_path(&SourceFileFullPath(path.clone())));
+        for file_coverage in report.files.values_mut() {
+            file_coverage
+                .functions
+                .retain(|name, _| self.includes_function(name));
+        }
+    }
+}
+
+/// Returns whether the function is defined in a test module, judging by its fully qualified name.
+/// Modules inside generic arguments are not taken into account.
+fn is_test_function(function_name: &str) -> bool {
+    ParsedFunctionName::parse(function_name).is_ok_and(|parsed| {
+        parsed
+            .module_path
+            .iter()
+            .any(|module| TEST_MODULE_NAMES.contains(module))
+    })
+}
+
+/// Segment of a glob pattern.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum GlobToken {
+    Char(char),
+    /// `?`
+    AnyChar,
+    /// `*`
+    Star,
+    /// `**` not followed by `/`.
+    DoubleStar,
+    /// `**/`, matching any number of whole directories, including none.
+    Directories,
+}
+
+/// Matches the path against the glob pattern in `O(pattern * path)` time, comparing characters.
+fn glob_matches(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<char> = pattern.replace('\\', "/").chars().collect();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    while index < pattern.len() {
+        let (token, len) = match pattern[index..] {
+            ['*', '*', '/', ..] => (GlobToken::Directories, 3),
+            ['*', '*', ..] => (GlobToken::DoubleStar, 2),
+            ['*', ..] => (GlobToken::Star, 1),
+            ['?', ..] => (GlobToken::AnyChar, 1),
+            [char, ..] => (GlobToken::Char(char), 1),
+            [] => unreachable!(),
+        };
+        tokens.push(token);
+        index += len;
+    }
+
+    let path: Vec<char> = path.chars().collect();
+    // `matches[j]` - whether the tokens after the current one match `path[j..]`.
+    let mut matches = vec![false; path.len() + 1];
+    matches[path.len()] = true;
+    for token in tokens.iter().rev() {
+        let mut current = vec![false; path.len() + 1];
+        // Whether `path[j..]` contains a `/` after which the tokens after the current one match.
+        let mut directories_end = false;
+        for j in (0..=path.len()).rev() {
+            let next_char = path.get(j).copied();
+            current[j] = match *token {
+                GlobToken::Char(expected) => next_char == Some(expected) && matches[j + 1],
+                GlobToken::AnyChar => next_char.is_some_and(|char| char != '/') && matches[j + 1],
+                GlobToken::Star => {
+                    matches[j] || (next_char.is_some_and(|char| char != '/') && current[j + 1])
+                }
+                GlobToken::DoubleStar => matches[j] || (next_char.is_some() && current[j + 1]),
+                GlobToken::Directories => {
+                    directories_end |= next_char == Some('/') && matches[j + 1];
+                    matches[j] || directories_end
+                }
+            };
+        }
+        matches = current;
+    }
+    matches[0]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::annotations::attribution::AttributionPolicy;
+    use crate::coverage_report::FileFunction;
+    use crate::coverage_report::test::{annotations, location};
+    use cairo_lang_sierra::program::StatementIdx;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches(
+            "/project/src/*.cairo",
+            "/project/src/lib.cairo"
+        ));
+        assert!(!glob_matches(
+            "/project/src/*.cairo",
+            "/project/src/a/lib.cairo"
+        ));
+        assert!(glob_matches(
+            "**/src/**/*.cairo",
+            "/project/src/a/b/lib.cairo"
+        ));
+        assert!(glob_matches("**/src/**/*.cairo", "/project/src/lib.cairo"));
+        assert!(glob_matches("**/tests/**", "/project/tests/test_a.cairo"));
+        assert!(!glob_matches("**/tests/**", "/project/src/tests.cairo"));
+        assert!(glob_matches(
+            "/project/src/lib?.cairo",
+            "/project/src/lib2.cairo"
+        ));
+        assert!(!glob_matches(
+            "/project/src/lib?.cairo",
+            "/project/src/lib.cairo"
+        ));
+        assert!(glob_matches(
+            "/project/src/ż?.cairo",
+            "/project/src/żó.cairo"
+        ));
+        assert!(glob_matches("**", "/project/src/lib.cairo"));
+        assert!(glob_matches("/project/**/lib.cairo", "/project/lib.cairo"));
+        assert!(!glob_matches(
+            "/project/**/lib.cairo",
+            "/project/src/mylib.cairo"
+        ));
+    }
+
+    #[test]
+    fn test_glob_matches_does_not_backtrack() {
+        let pattern = "**/a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*b";
+        let path = format!("/{}", "a".repeat(200));
+        assert!(!glob_matches(pattern, &path));
+    }
+
+    #[test]
+    fn test_is_test_function() {
+        assert!(is_test_function("my_package::tests::test_sum"));
+        assert!(is_test_function("my_package::math::test::it_works"));
+        assert!(!is_test_function("my_package::tests"));
+        assert!(!is_test_function("my_package::testing::helper"));
+        assert!(!is_test_function(
+            "my_package::foo<my_package::tests::Helper>"
+        ));
+        assert!(is_test_function(
+            "my_package::tests::HelperImpl::<core::felt252>::setup"
+        ));
+    }
+
+    #[test]
+    fn test_filter_annotations() {
+        let filter = CoverageFilter {
+            exclude: vec!["**/generated/**".to_string()],
+            exclude_macro_generated: true,
+            exclude_tests: true,
+            exclude_dependencies: true,
+            ..Default::default()
+        };
+        let dependency = "/home/user/.cache/scarb/registry/std/v2.11.0/core/src/lib.cairo";
+        let annotations = annotations([
+            (
+                0,
+                vec![
+                    location(dependency, 1, Some(false)),
+                    location("/project/src/lib.cairo", 1, Some(false)),
+                ],
+            ),
+            (1, vec![location("/project/src/lib.cairo", 2, Some(true))]),
+            (2, vec![location("/project/src/generated/a.cairo", 3, None)]),
+            (3, vec![location("/project/src/lib.cairo", 10, None)]),
+        ]);
+        let profiler_annotations = ProfilerAnnotationsV1 {
+            statements_functions: HashMap::from([
+                (
+                    StatementIdx(0),
+                    vec![FunctionName("core::assert".to_string())],
+                ),
+                (
+                    StatementIdx(3),
+                    vec![FunctionName("my_package::tests::test_a".to_string())],
+                ),
+            ]),
+        };
+
+        let filtered = filter.filter_annotations(&annotations, Some(&profiler_annotations));
+
+        assert_eq!(
+            filtered.statements_code_locations,
+            HashMap::from([(
+                StatementIdx(0),
+                vec![location("/project/src/lib.cairo", 1, Some(false))]
+            )])
+        );
+
+        let without_profiler = filter.filter_annotations(&annotations, None);
+        assert!(
+            without_profiler
+                .statements_code_locations
+                .contains_key(&StatementIdx(3))
+        );
+    }
+
+    #[test]
+    fn test_includes_dependencies() {
+        let registry = SourceFileFullPath(
+            "/home/user/.cache/scarb/registry/std/v2.11.0/core/src/lib.cairo".to_string(),
+        );
+        let git = SourceFileFullPath(
+            "/home/user/.cache/scarb/git/checkouts/dep-1a2b/3c4d/src/lib.cairo".to_string(),
+        );
+        let custom = SourceFileFullPath(
+            "/opt/scarb-cache/registry/std/v2.11.0/core/src/lib.cairo".to_string(),
+        );
+        let project = SourceFileFullPath("/project/src/lib.cairo".to_string());
+
+        let filter = CoverageFilter {
+            exclude_dependencies: true,
+            ..Default::default()
+        };
+        assert!(!filter.includes_path(&registry));
+        assert!(!filter.includes_path(&git));
+        assert!(filter.includes_path(&custom));
+        assert!(filter.includes_path(&project));
+
+        let filter = CoverageFilter {
+            exclude_dependencies: true,
+            scarb_cache_dir: Some(Utf8PathBuf::from("/opt/scarb-cache")),
+            ..Default::default()
+        };
+        assert!(!filter.includes_path(&custom));
+        assert!(filter.includes_path(&registry));
+        assert!(filter.includes_path(&project));
+    }
+
+    #[test]
+    fn test_filter_report() {
+        let filter = CoverageFilter {
+            include: vec!["/project/src/**".to_string()],
+            exclude_tests: true,
+            ..Default::default()
+        };
+        let mut report = CoverageReport::new(
+            &annotations([
+                (0, vec![location("/project/src/lib.cairo", 1, None)]),
+                (1, vec![location("/project/tests/test.cairo", 1, None)]),
+            ]),
+            &HashMap::new(),
+            AttributionPolicy::AllFrames,
+        );
+        let functions = &mut report
+            .files
+            .get_mut("/project/src/lib.cairo")
+            .unwrap()
+            .functions;
+        functions.insert("my_package::sum".to_string(), FileFunction::default());
+        functions.insert(
+            "my_package::tests::test_sum".to_string(),
+            FileFunction::default(),
+        );
+
+        filter.filter_report(&mut report);
+
+        assert_eq!(
+            report.files.keys().collect::<Vec<_>>(),
+            vec!["/project/src/lib.cairo"]
+        );
+        assert_eq!(
+            report.files["/project/src/lib.cairo"]
+                .functions
+                .keys()
+                .collect::<Vec<_>>(),
+            vec!["my_package::sum"]
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use crate::annotations::coverage::{SourceCodeSpan, SourceFileFullPath};
+use crate::annotations::debugger::{DebuggerAnnotationsV1, SierraFunctionId};
+use cairo_lang_sierra::program::{Program, StatementIdx};
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Coverage of sierra functions: whether and how many times each of them was called.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FunctionCoverage {
+    /// Functions of the program, sorted by their entry points.
+    pub functions: Vec<FunctionCalls>,
+}
+
+/// A sierra function and the number of times it was called.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FunctionCalls {
+    pub id: SierraFunctionId,
+    /// Debug name of the function, available if debug info was populated into the program
+    /// with [`DebugInfo::populate`](cairo_lang_sierra::debug_info::DebugInfo::populate).
+    pub name: Option<String>,
+    /// Statements belonging to the function.
+    pub statements: Range<StatementIdx>,
+    /// Path to the user file the function comes from, if available in debugger annotations.
+    pub file_path: Option<SourceFileFullPath>,
+    /// Span of the function in the user file, if available in debugger annotations.
+    pub code_span: Option<SourceCodeSpan>,
+    /// Number of times the function was called.
+    pub calls: usize,
+}
+
+impl FunctionCoverage {
+    /// Builds the function coverage from the number of times each statement was executed.
+    ///
+    /// Functions occupy consecutive statements starting at their entry points, up to the entry point
+    /// of the next function. Statements which compile to no CASM instructions never show up in the trace,
+    /// so the number of calls is the number of executions of the first executed statement of a function.
+    #[must_use]
+    pub fn new(
+        program: &Program,
+        annotations: &DebuggerAnnotationsV1,
+        statement_hits: &HashMap<StatementIdx, usize>,
+    ) -> Self {
+        let mut entry_points: Vec<_> = program
+            .funcs
+            .iter()
+            .map(|function| (function.entry_point, &function.id))
+            .collect();
+        entry_points.sort_by_key(|(entry_point, _)| entry_point.0);
+
+        let ends = entry_points
+            .iter()
+            .skip(1)
+            .map(|(entry_point, _)| *entry_point)
+            .chain(std::iter::once(StatementIdx(program.statements.len())));
+
+        let functions = entry_points
+            .iter()
+            .zip(ends)
+            .map(|(&(start, function_id), end)| {
+                let id = SierraFunctionId(function_id.id);
+                let function_debug_info = annotations.functions_info.get(&id);
+                let calls = (start.0..end.0)
+                    .find_map(|statement| {
+                        statement_hits
+                            .get(&StatementIdx(statement))
+                            .copied()
+                            .filter(|&hits| hits > 0)
+                    })
+                    .unwrap_or_default();
+
+                FunctionCalls {
+                    id,
+                    name: function_id.debug_name.as_ref().map(ToString::to_string),
+                    statements: start..end,
+                    file_path: function_debug_info.map(|info| info.function_file_path.clone()),
+                    code_span: function_debug_info.map(|info| info.function_code_span.clone()),
+                    calls,
+                }
+            })
+            .collect();
+
+        Self { functions }
+    }
+
+    /// Returns the function the statement belongs to.
+    #[must_use]
+    pub fn function_of(&self, statement_idx: StatementIdx) -> Option<&FunctionCalls> {
+        let index = self
+            .functions
+            .partition_point(|function| function.statements.start.0 <= statement_idx.0)
+            .checked_sub(1)?;
+        self.functions
+            .get(index)
+            .filter(|function| statement_idx.0 < function.statements.end.0)
+    }
+
+    /// Functions that were never called.
+    pub fn uncalled(&self) -> impl Iterator<Item = &FunctionCalls> {
+        self.functions.iter().filter(|function| function.calls == 0)
+    }
+}
+
+impl FunctionCalls {
+    /// Name of the function to display, falling back to its id if the debug name is not available.
+    #[must_use]
+    pub fn display_name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("function_{}", self.id.0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::annotations::coverage::{ColumnNumber, LineNumber, SourceCodeLocation};
+    use crate::annotations::debugger::FunctionDebugInfo;
+    use cairo_lang_sierra::ids::FunctionId;
+    use cairo_lang_sierra::program::{Function, FunctionSignature, Statement};
+
+    fn function(id: FunctionId, entry_point: usize) -> Function {
+        Function {
+            id,
+            signature: FunctionSignature {
+                param_types: vec![],
+                ret_types: vec![],
+            },
+            params: vec![],
+            entry_point: StatementIdx(entry_point),
+        }
+    }
+
+    #[test]
+    fn test_function_coverage() {
+        let program = Program {
+            type_declarations: vec![],
+            libfunc_declarations: vec![],
+            statements: vec![Statement::Return(vec![]); 6],
+            funcs: vec![
+                function(FunctionId::from_string("b"), 3),
+                function(FunctionId::new(7), 0),
+            ],
+        };
+        let span = SourceCodeSpan {
+            start: SourceCodeLocation {
+                line: LineNumber(2),
+                col: ColumnNumber(0),
+            },
+            end: SourceCodeLocation {
+                line: LineNumber(4),
+                col: ColumnNumber(1),
+            },
+        };
+        let annotations = DebuggerAnnotationsV1 {
+            functions_info: HashMap::from([(
+                SierraFunctionId(7),
+                FunctionDebugInfo {
+                    function_file_path: SourceFileFullPath("/src/lib.cairo".to_string()),
+                    function_code_span: span.clone(),
+                    sierra_to_cairo_variable: HashMap::new(),
+                },
+            )]),
+        };
+        // The first statement of function 7 compiles to no instructions.
+        let statement_hits = HashMap::from([(StatementIdx(1), 2), (StatementIdx(2), 1)]);
+
+        let function_coverage = FunctionCoverage::new(&program, &annotations, &statement_hits);
+
+        let [first, second] = function_coverage.functions.as_slice() else {
+            panic!("expected two functions");
+        };
+        assert_eq!(first.id, SierraFunctionId(7));
+        assert_eq!(first.statements, StatementIdx(0)..StatementIdx(3));
+        assert_eq!(first.calls, 2);
+        assert_eq!(first.code_span, Some(span));
+        assert_eq!(first.display_name(), "function_7");
+
+        assert_eq!(second.statements, StatementIdx(3)..StatementIdx(6));
+        assert_eq!(second.calls, 0);
+        assert_eq!(second.file_path, None);
+        assert_eq!(second.display_name(), "b");
+
+        assert_eq!(
+            function_coverage.uncalled().collect::<Vec<_>>(),
+            vec![second]
+        );
+        assert_eq!(function_coverage.function_of(StatementIdx(4)), Some(second));
+        assert_eq!(function_coverage.function_of(StatementIdx(6)), None);
+    }
+}
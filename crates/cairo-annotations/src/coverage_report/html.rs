@@ -0,0 +1,521 @@
+use crate::annotations::coverage::{LineNumber, SourceFileFullPath};
+use crate::coverage_report::{CoverageReport, CoverageSummary, FileCoverage};
+use crate::source_code::{SourceFile, SourceProvider};
+use camino::{Utf8Path, Utf8PathBuf};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+use std::{fs, io};
+
+const INDEX_PAGE: &str = "index.html";
+const FILES_DIR: &str = "files";
+/// Characters that are not allowed in file names on Windows.
+const RESERVED_CHARS: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.4em; }
+h2 { font-size: 1.1em; margin-top: 2em; }
+table { border-collapse: collapse; }
+th, td { padding: 2px 10px; text-align: left; }
+th { background: #e8e8e8; }
+td.number { text-align: right; }
+.high { background: #a7fc9d; }
+.medium { background: #ffea20; }
+.low { background: #ff6230; }
+table.source { width: 100%; font-family: monospace; }
+table.source td { padding: 0 8px; white-space: pre; }
+table.source td.line-number, table.source td.hits { text-align: right; color: #777; width: 1%; }
+tr.hit td.code { background: #dcf4d5; }
+tr.miss td.code { background: #fbd3cd; }
+tr.macro td.code { background: #e4e4f7; }
+.legend span { padding: 2px 8px; margin-right: 8px; }
+";
+
+/// Static, self-contained HTML coverage report.
+///
+/// Consists of an index page with per-directory and per-file percentages,
+/// and a page with annotated source code for every file.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct HtmlReport {
+    pages: BTreeMap<Utf8PathBuf, String>,
+}
+
+impl HtmlReport {
+    /// Renders the pages of the report.
+    ///
+    /// Sources are read using `provider`. Pages of files that cannot be read only list the coverage data.
+    #[must_use]
+    pub fn generate(report: &CoverageReport, provider: &impl SourceProvider) -> Self {
+        let display_paths = display_paths(report.files.keys());
+        let page_paths = file_page_paths(&display_paths);
+
+        let mut pages = BTreeMap::new();
+        for (path, file_coverage) in &report.files {
+            let page_path = &page_paths[path];
+            let source = SourceFile::read(provider, &SourceFileFullPath(path.clone())).ok();
+            pages.insert(
+                page_path.clone(),
+                render_file_page(
+                    &display_paths[path],
+                    page_path,
+                    file_coverage,
+                    source.as_ref(),
+                ),
+            );
+        }
+        pages.insert(
+            INDEX_PAGE.into(),
+            render_index_page(report, &display_paths, &page_paths),
+        );
+
+        Self { pages }
+    }
+
+    /// Rendered pages keyed by their path relative to the report directory.
+    #[must_use]
+    pub fn pages(&self) -> &BTreeMap<Utf8PathBuf, String> {
+        &self.pages
+    }
+
+    /// Writes all pages to `output_dir`, creating it if needed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a directory or a file cannot be written.
+    pub fn write(&self, output_dir: &Utf8Path) -> io::Result<()> {
+        for (page_path, contents) in &self.pages {
+            let path = output_dir.join(page_path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, contents)?;
+        }
+        Ok(())
+    }
+}
+
+/// Shortens the paths by removing the directory common to all of them.
+fn display_paths<'a>(paths: impl Iterator<Item = &'a String> + Clone) -> BTreeMap<String, String> {
+    let directories = |path: &'a str| {
+        let mut components = path.split(['/', '\\']).collect::<Vec<_>>();
+        components.pop();
+        components
+    };
+
+    let common_prefix_len = paths
+        .clone()
+        .map(|path| directories(path))
+        .reduce(|common, components| {
+            common
+                .into_iter()
+                .zip(components)
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect()
+        })
+        .map_or(0, |common| common.len());
+
+    paths
+        .map(|path| {
+            let display_path = path
+                .split(['/', '\\'])
+                .skip(common_prefix_len)
+                .collect::<Vec<_>>()
+                .join("/");
+            (path.clone(), display_path)
+        })
+        .collect()
+}
+
+/// Assigns a page to every file, keyed by the original path.
+///
+/// Components and characters that cannot be used in a path on any platform are replaced with `_`,
+/// and pages that would be shared by multiple files get a numeric suffix, like
+/// `files/_/lib.cairo-1.html`. Pages are compared case-insensitively, as on macOS and Windows.
+fn file_page_paths(display_paths: &BTreeMap<String, String>) -> BTreeMap<String, Utf8PathBuf> {
+    let mut sanitized_paths = display_paths
+        .iter()
+        .map(|(path, display_path)| {
+            let sanitized = display_path
+                .split('/')
+                .map(sanitize_component)
+                .collect::<Vec<_>>()
+                .join("/");
+            (path, display_path, sanitized)
+        })
+        .collect::<Vec<_>>();
+    // Files whose paths did not need sanitizing keep their names.
+    sanitized_paths.sort_by_key(|(path, display_path, sanitized)| {
+        (sanitized != *display_path, *display_path, *path)
+    });
+
+    let mut taken = HashSet::new();
+    let mut page_paths = BTreeMap::new();
+    for (path, _, sanitized) in sanitized_paths {
+        let mut page_path = Utf8PathBuf::from(FILES_DIR).join(format!("{sanitized}.html"));
+        let mut index = 0;
+        while !taken.insert(page_path.as_str().to_lowercase()) {
+            index += 1;
+            page_path = Utf8PathBuf::from(FILES_DIR).join(format!("{sanitized}-{index}.html"));
+        }
+        page_paths.insert(path.clone(), page_path);
+    }
+    page_paths
+}
+
+fn sanitize_component(component: &str) -> String {
+    match component {
+        "" | "." | ".." => "_".to_string(),
+        component => {
+            let sanitized = component.replace(
+                |char: char| RESERVED_CHARS.contains(&char) || char.is_control(),
+                "_",
+            );
+            // Windows drops trailing dots and spaces from file names.
+            let trimmed = sanitized.trim_end_matches(['.', ' ']);
+            format!("{trimmed}{}", "_".repeat(sanitized.len() - trimmed.len()))
+        }
+    }
+}
+
+/// Percent-encodes the components of a relative page path for use in a URL.
+fn page_url(page_path: &Utf8Path) -> String {
+    let mut url = String::new();
+    for (index, component) in page_path.components().enumerate() {
+        if index > 0 {
+            url.push('/');
+        }
+        for byte in component.as_str().bytes() {
+            if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+                url.push(char::from(byte));
+            } else {
+                let _ = write!(url, "%{byte:02X}");
+            }
+        }
+    }
+    url
+}
+
+fn directory_of(display_path: &str) -> &str {
+    display_path.rsplit_once('/').map_or(".", |(dir, _)| dir)
+}
+
+fn render_index_page(
+    report: &CoverageReport,
+    display_paths: &BTreeMap<String, String>,
+    page_paths: &BTreeMap<String, Utf8PathBuf>,
+) -> String {
+    let mut directories = BTreeMap::<&str, Vec<CoverageSummary>>::new();
+    for (path, file_coverage) in &report.files {
+        directories
+            .entry(directory_of(&display_paths[path]))
+            .or_default()
+            .push(file_coverage.summary());
+    }
+
+    let mut body = String::new();
+    body.push_str("<h1>Coverage report</h1>\n");
+    render_summary_table(&mut body, "Total", report.summary());
+
+    body.push_str("<h2>Directories</h2>\n<table>\n");
+    render_header_row(&mut body, "Directory");
+    for (directory, summaries) in directories {
+        render_summary_row(&mut body, &escape(directory), summaries.into_iter().sum());
+    }
+    body.push_str("</table>\n");
+
+    body.push_str("<h2>Files</h2>\n<table>\n");
+    render_header_row(&mut body, "File");
+    let files = report
+        .files
+        .iter()
+        .map(|(path, file_coverage)| ((&display_paths[path], path), file_coverage))
+        .collect::<BTreeMap<_, _>>();
+    for ((display_path, path), file_coverage) in files {
+        let link = format!(
+            "<a href=\"{}\">{}</a>",
+            escape(&page_url(&page_paths[path])),
+            escape(display_path)
+        );
+        render_summary_row(&mut body, &link, file_coverage.summary());
+    }
+    body.push_str("</table>\n");
+
+    render_page("Coverage report", &body)
+}
+
+fn render_file_page(
+    display_path: &str,
+    page_path: &Utf8Path,
+    file_coverage: &FileCoverage,
+    source: Option<&SourceFile>,
+) -> String {
+    let depth = page_path.components().count() - 1;
+    let index_link = format!("{}{INDEX_PAGE}", "../".repeat(depth));
+
+    let mut body = String::new();
+    let _ = writeln!(
+        body,
+        "<p><a href=\"{index_link}\">Coverage report</a> / {}</p>",
+        escape(display_path)
+    );
+    let _ = writeln!(body, "<h1>{}</h1>", escape(display_path));
+    render_summary_table(&mut body, "Lines", file_coverage.summary());
+    body.push_str(
+        "<p class=\"legend\"><span class=\"high\">hit</span><span class=\"low\">not hit</span>\
+         <span style=\"background: #e4e4f7\">macro-generated</span></p>\n",
+    );
+
+    let lines: Vec<(LineNumber, &str)> = if let Some(source) = source {
+        source
+            .lines()
+            .enumerate()
+            .map(|(line, text)| (LineNumber(line), text))
+            .collect()
+    } else {
+        body.push_str("<p>Source code is not available.</p>\n");
+        file_coverage.lines.keys().map(|&line| (line, "")).collect()
+    };
+
+    body.push_str("<table class=\"source\">\n");
+    for (line, text) in lines {
+        let (class, hits) = match file_coverage.lines.get(&line) {
+            Some(line_coverage) if line_coverage.macro_generated => {
+                ("macro", line_coverage.hits.to_string())
+            }
+            Some(line_coverage) if line_coverage.hits > 0 => {
+                ("hit", line_coverage.hits.to_string())
+            }
+            Some(_) => ("miss", "0".to_string()),
+            None => ("", String::new()),
+        };
+        let _ = writeln!(
+            body,
+            "<tr class=\"{class}\"><td class=\"line-number\">{}</td><td class=\"hits\">{hits}</td>\
+             <td class=\"code\">{}</td></tr>",
+            line.0 + 1,
+            escape(text)
+        );
+    }
+    body.push_str("</table>\n");
+
+    render_page(&format!("Coverage - {display_path}"), &body)
+}
+
+fn render_summary_table(body: &mut String, label: &str, summary: CoverageSummary) {
+    body.push_str("<table>\n");
+    render_header_row(body, "");
+    render_summary_row(body, label, summary);
+    body.push_str("</table>\n");
+}
+
+fn render_header_row(body: &mut String, label: &str) {
+    let _ = writeln!(
+        body,
+        "<tr><th>{label}</th><th>Hit</th><th>Total</th><th>Coverage</th></tr>"
+    );
+}
+
+fn render_summary_row(body: &mut String, label: &str, summary: CoverageSummary) {
+    let percentage = summary.percentage();
+    let class = if percentage >= 90.0 {
+        "high"
+    } else if percentage >= 75.0 {
+        "medium"
+    } else {
+        "low"
+    };
+    let _ = writeln!(
+        body,
+        "<tr><td>{label}</td><td class=\"number\">{}</td><td class=\"number\">{}</td>\
+         <td class=\"number {class}\">{percentage:.1}%</td></tr>",
+        summary.lines_hit, summary.lines_found
+    );
+}
+
+fn render_page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+         <style>{STYLE}</style>\n</head>\n<body>\n{body}</body>\n</html>\n",
+        escape(title)
+    )
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            char => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::annotations::attribution::AttributionPolicy;
+    use crate::coverage_report::test::{annotations, location};
+    use crate::source_code::InMemorySourceProvider;
+    use cairo_lang_sierra::program::StatementIdx;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_display_paths() {
+        let paths = [
+            "/project/src/lib.cairo".to_string(),
+            "/project/src/utils/math.cairo".to_string(),
+        ];
+        let display_paths = display_paths(paths.iter());
+        assert_eq!(display_paths[&paths[0]], "lib.cairo");
+        assert_eq!(display_paths[&paths[1]], "utils/math.cairo");
+    }
+
+    #[test]
+    fn test_generate() {
+        let annotations = annotations([
+            (0, vec![location("/project/src/lib.cairo", 0, Some(false))]),
+            (1, vec![location("/project/src/lib.cairo", 1, Some(false))]),
+            (2, vec![location("/project/src/lib.cairo", 2, Some(true))]),
+            (3, vec![location("/project/src/utils/a.cairo", 0, None)]),
+        ]);
+        let report = CoverageReport::new(
+            &annotations,
+            &HashMap::from([(StatementIdx(0), 3)]),
+            AttributionPolicy::AllFrames,
+        );
+
+        let mut provider = InMemorySourceProvider::new();
+        provider.insert(
+            "/project/src/lib.cairo",
+            "fn a() -> bool {\n    1 < 2\n}\nassert!(a());\n",
+        );
+
+        let html_report = HtmlReport::generate(&report, &provider);
+        let pages = html_report.pages();
+        assert_eq!(
+            pages.keys().collect::<Vec<_>>(),
+            vec![
+                "files/lib.cairo.html",
+                "files/utils/a.cairo.html",
+                "index.html"
+            ]
+        );
+
+        let index = &pages[Utf8Path::new("index.html")];
+        assert!(index.contains("<a href=\"files/utils/a.cairo.html\">utils/a.cairo</a>"));
+        assert!(index.contains("<td>utils</td>"));
+        assert!(index.contains("33.3%"));
+
+        let lib = &pages[Utf8Path::new("files/lib.cairo.html")];
+        assert!(lib.contains("<a href=\"../index.html\">"));
+        assert!(lib.contains(
+            "<tr class=\"hit\"><td class=\"line-number\">1</td><td class=\"hits\">3</td>"
+        ));
+        assert!(lib.contains("<tr class=\"miss\"><td class=\"line-number\">2</td><td class=\"hits\">0</td><td class=\"code\">    1 &lt; 2</td>"));
+        assert!(lib.contains("<tr class=\"macro\"><td class=\"line-number\">3</td>"));
+        assert!(lib.contains("<tr class=\"\"><td class=\"line-number\">4</td>"));
+
+        let missing = &pages[Utf8Path::new("files/utils/a.cairo.html")];
+        assert!(missing.contains("<a href=\"../../index.html\">"));
+        assert!(missing.contains("Source code is not available."));
+    }
+
+    #[test]
+    fn test_file_page_paths_are_unique() {
+        let display_paths = BTreeMap::from([
+            ("/a/../lib.cairo".to_string(), "../lib.cairo".to_string()),
+            ("/a/./lib.cairo".to_string(), "./lib.cairo".to_string()),
+            ("/a/_/lib.cairo".to_string(), "_/lib.cairo".to_string()),
+            ("/a//lib.cairo".to_string(), "/lib.cairo".to_string()),
+        ]);
+
+        let page_paths = file_page_paths(&display_paths);
+
+        assert_eq!(
+            page_paths.values().collect::<HashSet<_>>().len(),
+            display_paths.len()
+        );
+        assert_eq!(page_paths["/a/_/lib.cairo"], "files/_/lib.cairo.html");
+        assert_eq!(page_paths["/a/../lib.cairo"], "files/_/lib.cairo-1.html");
+        assert_eq!(page_paths["/a/./lib.cairo"], "files/_/lib.cairo-2.html");
+        assert_eq!(page_paths["/a//lib.cairo"], "files/_/lib.cairo-3.html");
+    }
+
+    #[test]
+    fn test_file_page_paths_are_case_insensitive() {
+        let display_paths = BTreeMap::from([
+            ("/a/src/Lib.cairo".to_string(), "src/Lib.cairo".to_string()),
+            ("/a/src/lib.cairo".to_string(), "src/lib.cairo".to_string()),
+            ("/a/SRC/lib.cairo".to_string(), "SRC/lib.cairo".to_string()),
+        ]);
+
+        let page_paths = file_page_paths(&display_paths);
+
+        assert_eq!(
+            page_paths
+                .values()
+                .map(|path| path.as_str().to_lowercase())
+                .collect::<HashSet<_>>()
+                .len(),
+            display_paths.len()
+        );
+    }
+
+    #[test]
+    fn test_file_page_paths_are_valid_on_windows() {
+        let display_paths = BTreeMap::from([
+            (
+                "/a/x<>:\"|?*.cairo".to_string(),
+                "x<>:\"|?*.cairo".to_string(),
+            ),
+            (
+                "/a/dir. /lib.cairo".to_string(),
+                "dir. /lib.cairo".to_string(),
+            ),
+            ("/a/tab\t.cairo".to_string(), "tab\t.cairo".to_string()),
+        ]);
+
+        let page_paths = file_page_paths(&display_paths);
+
+        assert_eq!(
+            page_paths["/a/x<>:\"|?*.cairo"],
+            "files/x_______.cairo.html"
+        );
+        assert_eq!(
+            page_paths["/a/dir. /lib.cairo"],
+            "files/dir__/lib.cairo.html"
+        );
+        assert_eq!(page_paths["/a/tab\t.cairo"], "files/tab_.cairo.html");
+    }
+
+    #[test]
+    fn test_links_are_encoded() {
+        let report = CoverageReport::new(
+            &annotations([
+                (0, vec![location("/project/src/a b&c\"<d>.cairo", 0, None)]),
+                (1, vec![location("/project/src/#?%.cairo", 0, None)]),
+            ]),
+            &HashMap::new(),
+            AttributionPolicy::AllFrames,
+        );
+
+        let html_report = HtmlReport::generate(&report, &InMemorySourceProvider::new());
+        let index = &html_report.pages()[Utf8Path::new("index.html")];
+
+        assert!(index.contains(
+            "<a href=\"files/a%20b%26c__d_.cairo.html\">a b&amp;c&quot;&lt;d&gt;.cairo</a>"
+        ));
+        assert!(index.contains("<a href=\"files/%23_%25.cairo.html\">#?%.cairo</a>"));
+        assert!(
+            html_report
+                .pages()
+                .contains_key(Utf8Path::new("files/#_%.cairo.html"))
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use crate::coverage_report::CoverageReport;
+use std::fmt::Write;
+
+/// Renders the report in the LCOV tracefile format, as consumed by `genhtml`, Codecov and others.
+///
+/// Functions are emitted as `FN` and `FNDA` records, lines as `DA` records and branches as `BRDA` records,
+/// where the block number is the index of the branching sierra statement.
+/// Branches of statements that were never executed are marked with `-`.
+#[must_use]
+pub fn render_lcov(report: &CoverageReport) -> String {
+    let mut lcov = String::new();
+
+    for (path, file_coverage) in &report.files {
+        let summary = file_coverage.summary();
+        let _ = writeln!(lcov, "TN:");
+        let _ = writeln!(lcov, "SF:{path}");
+
+        for (name, function) in &file_coverage.functions {
+            let _ = writeln!(lcov, "FN:{},{name}", function.line.0 + 1);
+        }
+        for (name, function) in &file_coverage.functions {
+            let _ = writeln!(lcov, "FNDA:{},{name}", function.calls);
+        }
+        if summary.functions_found > 0 {
+            let _ = writeln!(lcov, "FNF:{}", summary.functions_found);
+            let _ = writeln!(lcov, "FNH:{}", summary.functions_hit);
+        }
+
+        for (line, statements) in &file_coverage.branches {
+            for (statement_idx, statement_branches) in statements {
+                for (branch, taken) in statement_branches.taken.iter().enumerate() {
+                    let taken = if statement_branches.executions == 0 {
+                        "-".to_string()
+                    } else {
+                        taken.to_string()
+                    };
+                    let _ = writeln!(
+                        lcov,
+                        "BRDA:{},{},{branch},{taken}",
+                        line.0 + 1,
+                        statement_idx.0
+                    );
+                }
+            }
+        }
+        if summary.branches_found > 0 {
+            let _ = writeln!(lcov, "BRF:{}", summary.branches_found);
+            let _ = writeln!(lcov, "BRH:{}", summary.branches_hit);
+        }
+
+        for (line, line_coverage) in &file_coverage.lines {
+            let _ = writeln!(lcov, "DA:{},{}", line.0 + 1, line_coverage.hits);
+        }
+        let _ = writeln!(lcov, "LF:{}", summary.lines_found);
+        let _ = writeln!(lcov, "LH:{}", summary.lines_hit);
+        let _ = writeln!(lcov, "end_of_record");
+    }
+
+    lcov
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::annotations::attribution::AttributionPolicy;
+    use crate::annotations::coverage::LineNumber;
+    use crate::coverage_report::FileFunction;
+    use crate::coverage_report::branches::{BranchCoverage, StatementBranches};
+    use crate::coverage_report::test::{annotations, location};
+    use cairo_lang_sierra::program::StatementIdx;
+    use std::collections::{BTreeMap, HashMap};
+
+    #[test]
+    fn test_render_lcov() {
+        let annotations = annotations([
+            (0, vec![location("/src/lib.cairo", 1, None)]),
+            (1, vec![location("/src/lib.cairo", 2, None)]),
+            (7, vec![location("/src/lib.cairo", 4, None)]),
+        ]);
+        let branch_coverage = BranchCoverage {
+            statements: BTreeMap::from([
+                (
+                    StatementIdx(0),
+                    StatementBranches {
+                        targets: vec![StatementIdx(1), StatementIdx(4)],
+                        executions: 2,
+                        taken: vec![2, 0],
+                    },
+                ),
+                (
+                    StatementIdx(7),
+                    StatementBranches {
+                        targets: vec![StatementIdx(8), StatementIdx(9)],
+                        executions: 0,
+                        taken: vec![0, 0],
+                    },
+                ),
+            ]),
+        };
+
+        let mut report = CoverageReport::new(
+            &annotations,
+            &HashMap::from([(StatementIdx(0), 2), (StatementIdx(1), 2)]),
+            AttributionPolicy::AllFrames,
+        );
+        report.add_branches(&annotations, &branch_coverage, AttributionPolicy::AllFrames);
+        report.files.get_mut("/src/lib.cairo").unwrap().functions = BTreeMap::from([
+            (
+                "lib::a".to_string(),
+                FileFunction {
+                    line: LineNumber(0),
+                    calls: 2,
+                },
+            ),
+            (
+                "lib::b".to_string(),
+                FileFunction {
+                    line: LineNumber(3),
+                    calls: 0,
+                },
+            ),
+        ]);
+
+        assert_eq!(
+            render_lcov(&report),
+            "TN:\n\
+             SF:/src/lib.cairo\n\
+             FN:1,lib::a\n\
+             FN:4,lib::b\n\
+             FNDA:2,lib::a\n\
+             FNDA:0,lib::b\n\
+             FNF:2\n\
+             FNH:1\n\
+             BRDA:2,0,0,2\n\
+             BRDA:2,0,1,0\n\
+             BRDA:5,7,0,-\n\
+             BRDA:5,7,1,-\n\
+             BRF:4\n\
+             BRH:1\n\
+             DA:2,2\n\
+             DA:3,2\n\
+             DA:5,0\n\
+             LF:3\n\
+             LH:2\n\
+             end_of_record\n"
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use crate::annotations::coverage::{LineNumber, SourceFileFullPath};
+use crate::coverage_report::CoverageReport;
+use crate::source_code::{SourceFile, SourceProvider};
+use std::collections::BTreeSet;
+
+/// Comment excluding the line it is placed on from coverage.
+pub const IGNORE_LINE_MARKER: &str = "coverage: ignore-line";
+/// Comment starting a block of lines excluded from coverage, including the line it is placed on.
+pub const IGNORE_START_MARKER: &str = "coverage: ignore-start";
+/// Comment ending a block of lines excluded from coverage, including the line it is placed on.
+pub const IGNORE_END_MARKER: &str = "coverage: ignore-end";
+
+/// Finds lines excluded from coverage with comment markers.
+///
+/// A line is excluded if it contains a `// coverage: ignore-line` comment
+/// or is between `// coverage: ignore-start` and `// coverage: ignore-end` comments.
+/// A block which is never ended lasts until the end of the file. `//` inside string literals does not start a comment.
+///
+/// # Example
+/// ```
+/// use cairo_annotations::annotations::coverage::LineNumber;
+/// use cairo_annotations::coverage_report::markers::ignored_lines;
+/// use cairo_annotations::source_code::SourceFile;
+///
+/// let source = SourceFile::new(
+///     "fn a() {\n    panic!(\"unreachable\"); // coverage: ignore-line\n}\n".to_string(),
+/// );
+/// assert_eq!(ignored_lines(&source).into_iter().collect::<Vec<_>>(), vec![LineNumber(1)]);
+/// ```
+#[must_use]
+pub fn ignored_lines(source: &SourceFile) -> BTreeSet<LineNumber> {
+    let mut ignored_lines = BTreeSet::new();
+    let mut in_ignored_block = false;
+
+    for (line, text) in source.lines().enumerate() {
+        let markers = comment_markers(text);
+        if markers.contains(&IGNORE_START_MARKER) {
+            in_ignored_block = true;
+        }
+        if in_ignored_block || markers.contains(&IGNORE_LINE_MARKER) {
+            ignored_lines.insert(LineNumber(line));
+        }
+        if markers.contains(&IGNORE_END_MARKER) {
+            in_ignored_block = false;
+        }
+    }
+
+    ignored_lines
+}
+
+/// Returns markers found in `//` comments of the line.
+fn comment_markers(line: &str) -> Vec<&'static str> {
+    let Some(comment_start) = comment_start(line) else {
+        return Vec::new();
+    };
+    let comment = line[comment_start + 2..]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    [IGNORE_LINE_MARKER, IGNORE_START_MARKER, IGNORE_END_MARKER]
+        .into_iter()
+        .filter(|marker| comment.contains(marker))
+        .collect()
+}
+
+/// Returns the byte offset of the `//` starting a comment in the line, skipping string and short string literals.
+fn comment_start(line: &str) -> Option<usize> {
+    let mut chars = line.char_indices().peekable();
+    let mut open_quote = None;
+    while let Some((offset, char)) = chars.next() {
+        match (open_quote, char) {
+            (Some(_), '\\') => {
+                chars.next();
+            }
+            (Some(quote), char) if char == quote => open_quote = None,
+            (None, '"' | '\'') => open_quote = Some(char),
+            (None, '/') if chars.peek().is_some_and(|(_, next)| *next == '/') => {
+                return Some(offset);
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+impl CoverageReport {
+    /// Removes lines excluded with comment markers from the report, together with their branches and
+    /// functions starting at them. See [`ignored_lines`] for the supported markers.
+    ///
+    /// Sources are read using `provider`. Files that cannot be read are left untouched.
+    pub fn remove_ignored_lines(&mut self, provider: &impl SourceProvider) {
+        for (path, file_coverage) in &mut self.files {
+            let Ok(source) = SourceFile::read(provider, &SourceFileFullPath(path.clone())) else {
+                continue;
+            };
+            let ignored_lines = ignored_lines(&source);
+
+            file_coverage
+                .lines
+                .retain(|line, _| !ignored_lines.contains(line));
+            file_coverage
+                .branches
+                .retain(|line, _| !ignored_lines.contains(line));
+            file_coverage
+                .functions
+                .retain(|_, function| !ignored_lines.contains(&function.line));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::annotations::attribution::AttributionPolicy;
+    use crate::coverage_report::FileFunction;
+    use crate::coverage_report::test::{annotations, location};
+    use crate::source_code::InMemorySourceProvider;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_ignored_lines() {
+        let source = SourceFile::new(
+            [
+                "fn a() {",
+                "    let x = 1; //coverage:   ignore-line",
+                "    // coverage: ignore-start",
+                "    let y = 2;",
+                "    // coverage: ignore-end",
+                "    let z = 3;",
+                "    // coverage: ignore-end",
+                "    // coverage: ignore-start",
+                "    let w = 4;",
+            ]
+            .join("\n"),
+        );
+
+        assert_eq!(
+            ignored_lines(&source).into_iter().collect::<Vec<_>>(),
+            [1, 2, 3, 4, 7, 8].map(LineNumber).to_vec()
+        );
+    }
+
+    #[test]
+    fn test_markers_in_string_literals() {
+        let source = SourceFile::new(
+            [
+                "let s = \"// coverage: ignore-start\";",
+                "let t = 'a\\'// coverage: ignore-line';",
+                "let u = \"a\\\"b\"; // coverage: ignore-line",
+                "let v = 1;",
+            ]
+            .join("\n"),
+        );
+
+        assert_eq!(
+            ignored_lines(&source).into_iter().collect::<Vec<_>>(),
+            vec![LineNumber(2)]
+        );
+    }
+
+    #[test]
+    fn test_remove_ignored_lines() {
+        let mut provider = InMemorySourceProvider::new();
+        provider.insert(
+            "/src/lib.cairo",
+            "fn a() {\n    1\n}\nfn b() { // coverage: ignore-line\n}\n",
+        );
+        let mut report = CoverageReport::new(
+            &annotations([
+                (0, vec![location("/src/lib.cairo", 1, None)]),
+                (1, vec![location("/src/lib.cairo[assert_macro]", 3, None)]),
+                (2, vec![location("/src/missing.cairo", 3, None)]),
+            ]),
+            &HashMap::new(),
+            AttributionPolicy::AllFrames,
+        );
+        let functions = &mut report.files.get_mut("/src/lib.cairo").unwrap().functions;
+        functions.insert(
+            "lib::a".to_string(),
+            FileFunction {
+                line: LineNumber(0),
+                calls: 0,
+            },
+        );
+        functions.insert(
+            "lib::b".to_string(),
+            FileFunction {
+                line: LineNumber(3),
+                calls: 0,
+            },
+        );
+
+        report.remove_ignored_lines(&provider);
+
+        let file = &report.files["/src/lib.cairo"];
+        assert_eq!(file.lines.keys().collect::<Vec<_>>(), vec![&LineNumber(1)]);
+        assert_eq!(file.functions.keys().collect::<Vec<_>>(), vec!["lib::a"]);
+        assert_eq!(report.files["/src/missing.cairo"].lines.len(), 1);
+    }
+}
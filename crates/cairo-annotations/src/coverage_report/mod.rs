@@ -0,0 +1,372 @@
+use crate::annotations::attribution::AttributionPolicy;
+use crate::annotations::coverage::{CodeLocation, CoverageAnnotationsV1, LineNumber};
+use crate::coverage_report::branches::{BranchCoverage, StatementBranches};
+use crate::coverage_report::functions::FunctionCoverage;
+use cairo_lang_sierra::program::StatementIdx;
+use std::collections::{BTreeMap, HashMap};
+
+pub mod branches;
+pub mod filter;
+pub mod functions;
+pub mod html;
+pub mod lcov;
+pub mod markers;
+
+/// Line coverage of Cairo source files, built from coverage annotations and statement hits.
+///
+/// Files are identified by their real path, i.e. with virtual file markings removed.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CoverageReport {
+    pub files: BTreeMap<String, FileCoverage>,
+}
+
+/// Coverage of lines in a single Cairo source file.
+///
+/// Only lines that some sierra statement maps to are present.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FileCoverage {
+    pub lines: BTreeMap<LineNumber, LineCoverage>,
+    /// Branching statements mapped to each line. Empty unless added with [`CoverageReport::add_branches`].
+    pub branches: BTreeMap<LineNumber, BTreeMap<StatementIdx, StatementBranches>>,
+    /// Functions defined in the file, by name. Empty unless added with [`CoverageReport::add_functions`].
+    pub functions: BTreeMap<String, FileFunction>,
+}
+
+/// A function defined in a Cairo source file.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FileFunction {
+    /// Line the function starts at.
+    pub line: LineNumber,
+    /// Number of times the function was called.
+    pub calls: usize,
+}
+
+/// Coverage of a single line of Cairo code.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct LineCoverage {
+    /// Number of times the most executed statement mapped to this line was executed.
+    pub hits: usize,
+    /// Whether all statements mapped to this line were generated by a macro.
+    pub macro_generated: bool,
+}
+
+/// Number of lines, branches and functions found and hit, used for displaying coverage percentages.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CoverageSummary {
+    pub lines_found: usize,
+    pub lines_hit: usize,
+    pub branches_found: usize,
+    pub branches_hit: usize,
+    pub functions_found: usize,
+    pub functions_hit: usize,
+}
+
+impl CoverageReport {
+    /// Builds the report from coverage annotations and the number of times each statement was executed.
+    ///
+    /// Statements missing from `statement_hits` are treated as never executed.
+    /// Use [`count_statement_hits`](crate::count_statement_hits) to obtain `statement_hits` from a mapped trace.
+    /// Statements are attributed to the lines of the frames selected by `policy`.
+    #[must_use]
+    pub fn new(
+        annotations: &CoverageAnnotationsV1,
+        statement_hits: &HashMap<StatementIdx, usize>,
+        policy: AttributionPolicy,
+    ) -> Self {
+        let mut report = Self::default();
+        for statement_idx in annotations.statements_code_locations.keys() {
+            let hits = statement_hits
+                .get(statement_idx)
+                .copied()
+                .unwrap_or_default();
+            for code_location in annotations.resolve_locations(*statement_idx, policy) {
+                report.add_location(code_location, hits);
+            }
+        }
+        report
+    }
+
+    fn add_location(&mut self, CodeLocation(path, span, macro_flag): &CodeLocation, hits: usize) {
+        let (real_path, _) = path.remove_virtual_file_markings();
+        let file = self.files.entry(real_path.to_string()).or_default();
+
+        for line in span.start.line.0..=span.end.line.0 {
+            let macro_generated = *macro_flag == Some(true);
+            file.lines
+                .entry(LineNumber(line))
+                .and_modify(|line_coverage| {
+                    line_coverage.hits = line_coverage.hits.max(hits);
+                    line_coverage.macro_generated &= macro_generated;
+                })
+                .or_insert(LineCoverage {
+                    hits,
+                    macro_generated,
+                });
+        }
+    }
+
+    /// Attributes branches of branching statements to the lines of the frames selected by `policy`.
+    pub fn add_branches(
+        &mut self,
+        annotations: &CoverageAnnotationsV1,
+        branch_coverage: &BranchCoverage,
+        policy: AttributionPolicy,
+    ) {
+        for (statement_idx, statement_branches) in &branch_coverage.statements {
+            for CodeLocation(path, span, _) in annotations.resolve_locations(*statement_idx, policy)
+            {
+                let (real_path, _) = path.remove_virtual_file_markings();
+                self.files
+                    .entry(real_path.to_string())
+                    .or_default()
+                    .branches
+                    .entry(span.start.line)
+                    .or_default()
+                    .insert(*statement_idx, statement_branches.clone());
+            }
+        }
+    }
+
+    /// Adds functions with known locations to the files they are defined in,
+    /// including the ones that were never called.
+    ///
+    /// Calls of functions with the same name, e.g. different monomorphizations, are summed up.
+    pub fn add_functions(&mut self, function_coverage: &FunctionCoverage) {
+        for function in &function_coverage.functions {
+            let (Some(path), Some(span)) = (&function.file_path, &function.code_span) else {
+                continue;
+            };
+            let (real_path, _) = path.remove_virtual_file_markings();
+            self.files
+                .entry(real_path.to_string())
+                .or_default()
+                .functions
+                .entry(function.display_name())
+                .or_insert(FileFunction {
+                    line: span.start.line,
+                    calls: 0,
+                })
+                .calls += function.calls;
+        }
+    }
+
+    /// Summary of all files in the report.
+    #[must_use]
+    pub fn summary(&self) -> CoverageSummary {
+        self.files.values().map(FileCoverage::summary).sum()
+    }
+}
+
+impl FileCoverage {
+    #[must_use]
+    pub fn summary(&self) -> CoverageSummary {
+        CoverageSummary {
+            lines_found: self.lines.len(),
+            lines_hit: self
+                .lines
+                .values()
+                .filter(|line_coverage| line_coverage.hits > 0)
+                .count(),
+            branches_found: self
+                .statement_branches()
+                .map(|statement_branches| statement_branches.targets.len())
+                .sum(),
+            branches_hit: self
+                .statement_branches()
+                .map(StatementBranches::branches_hit)
+                .sum(),
+            functions_found: self.functions.len(),
+            functions_hit: self
+                .functions
+                .values()
+                .filter(|function| function.calls > 0)
+                .count(),
+        }
+    }
+
+    fn statement_branches(&self) -> impl Iterator<Item = &StatementBranches> {
+        self.branches.values().flat_map(BTreeMap::values)
+    }
+}
+
+impl CoverageSummary {
+    /// Percentage of hit lines, 100 if there are no lines.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn percentage(&self) -> f64 {
+        if self.lines_found == 0 {
+            100.0
+        } else {
+            self.lines_hit as f64 * 100.0 / self.lines_found as f64
+        }
+    }
+}
+
+impl std::iter::Sum for CoverageSummary {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |acc, summary| Self {
+            lines_found: acc.lines_found + summary.lines_found,
+            lines_hit: acc.lines_hit + summary.lines_hit,
+            branches_found: acc.branches_found + summary.branches_found,
+            branches_hit: acc.branches_hit + summary.branches_hit,
+            functions_found: acc.functions_found + summary.functions_found,
+            functions_hit: acc.functions_hit + summary.functions_hit,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::annotations::coverage::{
+        ColumnNumber, SourceCodeLocation, SourceCodeSpan, SourceFileFullPath,
+    };
+    use crate::annotations::debugger::SierraFunctionId;
+    use crate::coverage_report::functions::FunctionCalls;
+
+    pub(crate) fn location(path: &str, line: usize, macro_flag: Option<bool>) -> CodeLocation {
+        let location = SourceCodeLocation {
+            line: LineNumber(line),
+            col: ColumnNumber(0),
+        };
+        CodeLocation(
+            SourceFileFullPath(path.to_string()),
+            SourceCodeSpan {
+                start: location.clone(),
+                end: location,
+            },
+            macro_flag,
+        )
+    }
+
+    pub(crate) fn annotations(
+        statements: impl IntoIterator<Item = (usize, Vec<CodeLocation>)>,
+    ) -> CoverageAnnotationsV1 {
+        CoverageAnnotationsV1 {
+            statements_code_locations: statements
+                .into_iter()
+                .map(|(statement_idx, locations)| (StatementIdx(statement_idx), locations))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_report_from_annotations() {
+        let annotations = annotations([
+            (0, vec![location("/src/lib.cairo", 1, Some(false))]),
+            (1, vec![location("/src/lib.cairo", 1, Some(false))]),
+            (2, vec![location("/src/lib.cairo", 2, Some(true))]),
+            (3, vec![location("/src/lib.cairo[assert_macro]", 3, None)]),
+        ]);
+        let statement_hits = HashMap::from([(StatementIdx(0), 2), (StatementIdx(1), 5)]);
+
+        let report =
+            CoverageReport::new(&annotations, &statement_hits, AttributionPolicy::AllFrames);
+
+        let file = &report.files["/src/lib.cairo"];
+        assert_eq!(file.lines[&LineNumber(1)].hits, 5);
+        assert_eq!(
+            file.lines[&LineNumber(2)],
+            LineCoverage {
+                hits: 0,
+                macro_generated: true
+            }
+        );
+        assert!(!file.lines[&LineNumber(3)].macro_generated);
+        assert_eq!(
+            report.summary(),
+            CoverageSummary {
+                lines_found: 3,
+                lines_hit: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_add_branches() {
+        let annotations = annotations([(0, vec![location("/src/lib.cairo", 4, None)])]);
+        let statement_branches = StatementBranches {
+            targets: vec![StatementIdx(1), StatementIdx(5)],
+            executions: 3,
+            taken: vec![3, 0],
+        };
+        let branch_coverage = BranchCoverage {
+            statements: BTreeMap::from([(StatementIdx(0), statement_branches.clone())]),
+        };
+
+        let mut report =
+            CoverageReport::new(&annotations, &HashMap::new(), AttributionPolicy::AllFrames);
+        report.add_branches(&annotations, &branch_coverage, AttributionPolicy::AllFrames);
+
+        let file = &report.files["/src/lib.cairo"];
+        assert_eq!(
+            file.branches[&LineNumber(4)],
+            BTreeMap::from([(StatementIdx(0), statement_branches)])
+        );
+        assert_eq!(file.summary().branches_found, 2);
+        assert_eq!(file.summary().branches_hit, 1);
+    }
+
+    #[test]
+    fn test_add_functions() {
+        let function = |name: &str, line, calls| FunctionCalls {
+            id: SierraFunctionId(0),
+            name: Some(name.to_string()),
+            statements: StatementIdx(0)..StatementIdx(1),
+            file_path: Some(SourceFileFullPath("/src/lib.cairo".to_string())),
+            code_span: Some(location("/src/lib.cairo", line, None).1),
+            calls,
+        };
+        let function_coverage = FunctionCoverage {
+            functions: vec![
+                function("lib::a", 1, 2),
+                function("lib::a", 1, 3),
+                function("lib::b", 5, 0),
+                FunctionCalls {
+                    file_path: None,
+                    ..function("lib::c", 9, 1)
+                },
+            ],
+        };
+
+        let mut report = CoverageReport::default();
+        report.add_functions(&function_coverage);
+
+        let file = &report.files["/src/lib.cairo"];
+        assert_eq!(
+            file.functions,
+            BTreeMap::from([
+                (
+                    "lib::a".to_string(),
+                    FileFunction {
+                        line: LineNumber(1),
+                        calls: 5
+                    }
+                ),
+                (
+                    "lib::b".to_string(),
+                    FileFunction {
+                        line: LineNumber(5),
+                        calls: 0
+                    }
+                ),
+            ])
+        );
+        assert_eq!(file.summary().functions_found, 2);
+        assert_eq!(file.summary().functions_hit, 1);
+    }
+
+    #[test]
+    fn test_line_is_macro_generated_only_if_all_statements_are() {
+        let annotations = annotations([
+            (0, vec![location("/src/lib.cairo", 1, Some(true))]),
+            (1, vec![location("/src/lib.cairo", 1, Some(false))]),
+        ]);
+
+        let report =
+            CoverageReport::new(&annotations, &HashMap::new(), AttributionPolicy::AllFrames);
+
+        assert!(!report.files["/src/lib.cairo"].lines[&LineNumber(1)].macro_generated);
+    }
+}
@@ -1,11 +1,36 @@
 #[cfg(feature = "cairo-lang")]
 pub mod annotations;
+#[cfg(feature = "cairo-lang")]
+pub mod artifacts;
+#[cfg(feature = "cairo-lang")]
+pub mod contract_class;
+#[cfg(feature = "cairo-lang")]
+pub mod coverage_report;
 mod felt_deserialize;
 #[cfg(feature = "cairo-lang")]
+pub mod listing;
+#[cfg(feature = "cairo-lang")]
+mod map_pcs_to_casm_instructions;
+#[cfg(feature = "cairo-lang")]
 mod map_pcs_to_sierra_statement_ids;
+#[cfg(feature = "cairo-lang")]
+pub mod profiling;
+#[cfg(feature = "cairo-lang")]
+mod program_offset;
+#[cfg(feature = "cairo-lang")]
+pub mod source_code;
 pub mod trace_data;
 
+#[cfg(feature = "cairo-lang")]
+pub use map_pcs_to_casm_instructions::{
+    CasmMapper, InstructionMappingResult, InvokedLibfunc, MappedInstruction,
+};
 #[cfg(feature = "cairo-lang")]
 pub use map_pcs_to_sierra_statement_ids::{
-    MappingResult, map_pc_to_sierra_statement_id, map_pcs_to_sierra_statement_ids,
+    MappingResult, PcMapper, count_statement_hits, map_pc_to_sierra_statement_id,
+    map_pcs_to_sierra_statement_ids, statement_executions,
+};
+#[cfg(feature = "cairo-lang")]
+pub use program_offset::{
+    DetectedProgramOffset, OffsetConfidence, ProgramOffsetError, detect_program_offset,
 };
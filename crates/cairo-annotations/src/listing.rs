@@ -0,0 +1,263 @@
+use crate::annotations::coverage::{CodeLocation, CoverageAnnotationsV1};
+use crate::annotations::profiler::ProfilerAnnotationsV1;
+use crate::map_pcs_to_casm_instructions::instruction_text;
+use cairo_lang_sierra::ids::FunctionId;
+use cairo_lang_sierra::program::{Program, StatementIdx};
+use cairo_lang_sierra_to_casm::compiler::CairoProgram;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Optional information interleaved with the statements in a [`render_listing`] output.
+/// Missing information is omitted from the listing.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ListingAnnotations<'a> {
+    /// Source code locations of the statements.
+    pub coverage: Option<&'a CoverageAnnotationsV1>,
+    /// Stacks of (possibly inlined) functions that generated the statements.
+    pub profiler: Option<&'a ProfilerAnnotationsV1>,
+    /// Number of executions of the statements, e.g. from [`count_statement_hits`](crate::count_statement_hits).
+    pub statement_hits: Option<&'a HashMap<StatementIdx, usize>>,
+}
+
+/// Renders a listing of `program` that interleaves each Sierra statement with the CASM instructions compiled
+/// from it, similarly to `objdump -S`.
+///
+/// Statements are grouped under the functions they start, and followed by their source locations, function
+/// stacks (outermost first) and instructions, prefixed by their code offsets. If hit counts are provided,
+/// statements are prefixed by the number of times they were executed.
+///
+/// `cairo_program` must come from compiling `program` with
+/// [`compile`](cairo_lang_sierra_to_casm::compiler::compile).
+///
+/// ```text
+/// pkg::main:
+///   #0  felt252_add(a, b) -> (c);
+///         // /path/to/project/src/lib.cairo:2:5
+///         // pkg::main > core::felt252::add
+///   #1  store_temp<felt252>(c) -> (c);
+///         [0]  [ap + 0] = [fp + -4] + [fp + -3], ap++
+/// ```
+#[must_use]
+pub fn render_listing(
+    program: &Program,
+    cairo_program: &CairoProgram,
+    annotations: &ListingAnnotations<'_>,
+) -> String {
+    let functions: HashMap<usize, &FunctionId> = program
+        .funcs
+        .iter()
+        .map(|function| (function.entry_point.0, &function.id))
+        .collect();
+    let hits_width = annotations.statement_hits.map(|statement_hits| {
+        statement_hits
+            .values()
+            .max()
+            .copied()
+            .unwrap_or_default()
+            .to_string()
+            .len()
+    });
+
+    let mut instructions = cairo_program
+        .instructions
+        .iter()
+        .scan(0, |code_offset, instruction| {
+            let instruction_offset = *code_offset;
+            *code_offset += instruction.body.op_size();
+            Some((instruction_offset, instruction))
+        })
+        .peekable();
+    let statements_info = &cairo_program.debug_info.sierra_statement_info;
+
+    let mut listing = Listing {
+        text: String::new(),
+        hits_width,
+    };
+    for (index, statement) in program.statements.iter().enumerate() {
+        let statement_idx = StatementIdx(index);
+
+        if let Some(function_id) = functions.get(&index) {
+            if !listing.text.is_empty() {
+                listing.text.push('\n');
+            }
+            listing.line(None, 0, &format!("{function_id}:"));
+        }
+
+        let hits = annotations.statement_hits.map(|statement_hits| {
+            statement_hits
+                .get(&statement_idx)
+                .copied()
+                .unwrap_or_default()
+        });
+        listing.line(hits, 2, &format!("#{index}  {statement};"));
+
+        let locations = annotations
+            .coverage
+            .and_then(|coverage| coverage.statements_code_locations.get(&statement_idx));
+        for location in locations.into_iter().flatten() {
+            listing.line(None, 8, &format!("// {}", location_text(location)));
+        }
+
+        let function_stack = annotations
+            .profiler
+            .and_then(|profiler| profiler.statements_functions.get(&statement_idx));
+        if let Some(function_stack) = function_stack.filter(|stack| !stack.is_empty()) {
+            let names: Vec<&str> = function_stack
+                .iter()
+                .rev()
+                .map(|function_name| function_name.0.as_str())
+                .collect();
+            listing.line(None, 8, &format!("// {}", names.join(" > ")));
+        }
+
+        let statement_end = statements_info
+            .get(index + 1)
+            .map_or(usize::MAX, |next_statement| next_statement.start_offset);
+        while let Some((code_offset, instruction)) =
+            instructions.next_if(|&(code_offset, _)| code_offset < statement_end)
+        {
+            listing.line(
+                None,
+                8,
+                &format!("[{code_offset}]  {}", instruction_text(instruction)),
+            );
+        }
+    }
+    listing.text
+}
+
+/// Rendered listing, with an optional column of statement hit counts.
+struct Listing {
+    text: String,
+    hits_width: Option<usize>,
+}
+
+impl Listing {
+    fn line(&mut self, hits: Option<usize>, indent: usize, content: &str) {
+        if let Some(width) = self.hits_width {
+            let hits = hits.map(|hits| hits.to_string()).unwrap_or_default();
+            let _ = write!(self.text, "{hits:>width$} | ");
+        }
+        let _ = writeln!(self.text, "{:indent$}{content}", "");
+    }
+}
+
+/// Location in the `path:line:column` format, with 1-based line and column numbers.
+fn location_text(CodeLocation(path, span, macro_generated): &CodeLocation) -> String {
+    let mut text = format!("{path}:{}:{}", span.start.line.0 + 1, span.start.col.0 + 1);
+    if *macro_generated == Some(true) {
+        text.push_str(" (macro)");
+    }
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::annotations::coverage::{
+        ColumnNumber, LineNumber, SourceCodeLocation, SourceCodeSpan, SourceFileFullPath,
+    };
+    use crate::annotations::profiler::FunctionName;
+    use crate::map_pcs_to_casm_instructions::test::compile_program;
+
+    const PROGRAM: &str = "
+        type felt252 = felt252;
+
+        libfunc felt252_add = felt252_add;
+        libfunc store_temp<felt252> = store_temp<felt252>;
+
+        felt252_add(a, b) -> (c);
+        store_temp<felt252>(c) -> (c);
+        return(c);
+
+        pkg::main@0(a: felt252, b: felt252) -> (felt252);
+    ";
+
+    fn code_location(line: usize, col: usize, macro_generated: Option<bool>) -> CodeLocation {
+        let location = SourceCodeLocation {
+            line: LineNumber(line),
+            col: ColumnNumber(col),
+        };
+        CodeLocation(
+            SourceFileFullPath("/project/src/lib.cairo".to_string()),
+            SourceCodeSpan {
+                start: location.clone(),
+                end: location,
+            },
+            macro_generated,
+        )
+    }
+
+    #[test]
+    fn test_render_listing() {
+        let (program, cairo_program) = compile_program(PROGRAM);
+
+        let listing = render_listing(&program, &cairo_program, &ListingAnnotations::default());
+
+        assert_eq!(
+            listing,
+            [
+                "pkg::main:",
+                "  #0  felt252_add(a, b) -> (c);",
+                "  #1  store_temp<felt252>(c) -> (c);",
+                "        [0]  [ap + 0] = [fp + -4] + [fp + -3], ap++",
+                "  #2  return(c);",
+                "        [1]  ret",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_render_listing_with_annotations() {
+        let (program, cairo_program) = compile_program(PROGRAM);
+        let coverage = CoverageAnnotationsV1 {
+            statements_code_locations: HashMap::from([
+                (StatementIdx(0), vec![code_location(1, 4, Some(true))]),
+                (StatementIdx(1), vec![code_location(1, 4, None)]),
+            ]),
+        };
+        let profiler = ProfilerAnnotationsV1 {
+            statements_functions: HashMap::from([(
+                StatementIdx(0),
+                vec![
+                    FunctionName("core::felt252::add".to_string()),
+                    FunctionName("pkg::main".to_string()),
+                ],
+            )]),
+        };
+        let statement_hits = HashMap::from([
+            (StatementIdx(0), 12),
+            (StatementIdx(1), 12),
+            (StatementIdx(2), 3),
+        ]);
+
+        let listing = render_listing(
+            &program,
+            &cairo_program,
+            &ListingAnnotations {
+                coverage: Some(&coverage),
+                profiler: Some(&profiler),
+                statement_hits: Some(&statement_hits),
+            },
+        );
+
+        assert_eq!(
+            listing,
+            [
+                "   | pkg::main:",
+                "12 |   #0  felt252_add(a, b) -> (c);",
+                "   |         // /project/src/lib.cairo:2:5 (macro)",
+                "   |         // pkg::main > core::felt252::add",
+                "12 |   #1  store_temp<felt252>(c) -> (c);",
+                "   |         // /project/src/lib.cairo:2:5",
+                "   |         [0]  [ap + 0] = [fp + -4] + [fp + -3], ap++",
+                " 3 |   #2  return(c);",
+                "   |         [1]  ret",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use crate::map_pcs_to_sierra_statement_ids::{MappingResult, PcMapper, real_minimal_pc};
+use crate::trace_data::{CasmLevelInfo, TraceEntry};
+use cairo_lang_casm::instructions::Instruction;
+use cairo_lang_sierra::ids::ConcreteLibfuncId;
+use cairo_lang_sierra::program::{Program, Statement, StatementIdx};
+use cairo_lang_sierra_to_casm::compiler::CairoProgram;
+use std::collections::HashMap;
+
+/// Enum to represent the result of mapping a pc to a CASM instruction.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InstructionMappingResult<'a> {
+    /// The pc was successfully mapped to a CASM instruction.
+    Instruction(MappedInstruction<'a>),
+    /// The pc was not mapped because it was in the header of the program.
+    Header,
+    /// The pc was not mapped because it was outside the function area.
+    PcOutOfFunctionArea,
+}
+
+/// A CASM instruction together with the Sierra statement it was generated from.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MappedInstruction<'a> {
+    pub statement_idx: StatementIdx,
+    /// Libfunc invoked by the statement. `None` for return statements.
+    pub libfunc: Option<InvokedLibfunc<'a>>,
+    pub instruction: &'a Instruction,
+    /// Offset of the instruction from the first instruction of the statement, in bytecode felts.
+    pub offset_in_statement: usize,
+}
+
+/// A libfunc invoked by a Sierra statement.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct InvokedLibfunc<'a> {
+    pub id: &'a ConcreteLibfuncId,
+    /// Name of the generic libfunc, e.g. `u256_safe_divmod` or `store_temp`.
+    pub generic_name: &'a str,
+}
+
+/// Maps program counters to the CASM instructions of a compiled program and the Sierra libfunc
+/// invocations which generated them.
+#[derive(Clone, Debug)]
+pub struct CasmMapper<'a> {
+    program: &'a Program,
+    cairo_program: &'a CairoProgram,
+    pc_mapper: PcMapper,
+    /// Index of the instruction of each code offset.
+    instructions: Vec<usize>,
+    generic_names: HashMap<&'a ConcreteLibfuncId, &'a str>,
+    real_minimal_pc: usize,
+}
+
+impl From<&InstructionMappingResult<'_>> for MappingResult {
+    fn from(mapping_result: &InstructionMappingResult<'_>) -> Self {
+        match mapping_result {
+            InstructionMappingResult::Instruction(instruction) => {
+                MappingResult::SierraStatementIdx(instruction.statement_idx)
+            }
+            InstructionMappingResult::Header => MappingResult::Header,
+            InstructionMappingResult::PcOutOfFunctionArea => MappingResult::PcOutOfFunctionArea,
+        }
+    }
+}
+
+impl MappedInstruction<'_> {
+    /// Returns the instruction as CASM code, without its hints, e.g. `[ap + 0] = [fp + -3] + 1, ap++`.
+    #[must_use]
+    pub fn instruction_text(&self) -> String {
+        instruction_text(self.instruction)
+    }
+}
+
+/// Returns the instruction as CASM code, without its hints.
+pub(crate) fn instruction_text(instruction: &Instruction) -> String {
+    if instruction.inc_ap {
+        format!("{}, ap++", instruction.body)
+    } else {
+        instruction.body.to_string()
+    }
+}
+
+impl<'a> CasmMapper<'a> {
+    /// Creates a mapper for `cairo_program` compiled from `program`, whose first CASM instruction is at
+    /// `real_minimal_pc` in traces.
+    #[must_use]
+    pub fn new(
+        program: &'a Program,
+        cairo_program: &'a CairoProgram,
+        real_minimal_pc: usize,
+    ) -> Self {
+        let mut instructions = Vec::new();
+        for (instruction_idx, instruction) in cairo_program.instructions.iter().enumerate() {
+            instructions.resize(
+                instructions.len() + instruction.body.op_size(),
+                instruction_idx,
+            );
+        }
+
+        let generic_names = program
+            .libfunc_declarations
+            .iter()
+            .map(|declaration| (&declaration.id, declaration.long_id.generic_id.0.as_str()))
+            .collect();
+
+        Self {
+            program,
+            cairo_program,
+            pc_mapper: PcMapper::new(&cairo_program.debug_info, real_minimal_pc),
+            instructions,
+            generic_names,
+            real_minimal_pc,
+        }
+    }
+
+    /// Creates a mapper for the program a trace was run with, taking headers into account the same way as
+    /// [`map_pcs_to_sierra_statement_ids`](crate::map_pcs_to_sierra_statement_ids).
+    #[must_use]
+    pub fn for_trace(
+        program: &'a Program,
+        cairo_program: &'a CairoProgram,
+        casm_level_info: &CasmLevelInfo,
+    ) -> Self {
+        Self::new(program, cairo_program, real_minimal_pc(casm_level_info))
+    }
+
+    /// Maps a program counter to a CASM instruction.
+    #[must_use]
+    pub fn map_pc(&self, pc: usize) -> InstructionMappingResult<'a> {
+        let statement_idx = match self.pc_mapper.map_pc(pc) {
+            MappingResult::SierraStatementIdx(statement_idx) => statement_idx,
+            MappingResult::Header => return InstructionMappingResult::Header,
+            MappingResult::PcOutOfFunctionArea => {
+                return InstructionMappingResult::PcOutOfFunctionArea;
+            }
+        };
+        let code_offset = pc - self.real_minimal_pc;
+        let (Some(&instruction_idx), Some(statement_debug_info)) = (
+            self.instructions.get(code_offset),
+            self.cairo_program
+                .debug_info
+                .sierra_statement_info
+                .get(statement_idx.0),
+        ) else {
+            return InstructionMappingResult::PcOutOfFunctionArea;
+        };
+
+        let libfunc = match self.program.statements.get(statement_idx.0) {
+            Some(Statement::Invocation(invocation)) => Some(InvokedLibfunc {
+                id: &invocation.libfunc_id,
+                generic_name: self
+                    .generic_names
+                    .get(&invocation.libfunc_id)
+                    .copied()
+                    .unwrap_or_default(),
+            }),
+            Some(Statement::Return(_)) | None => None,
+        };
+
+        InstructionMappingResult::Instruction(MappedInstruction {
+            statement_idx,
+            libfunc,
+            instruction: &self.cairo_program.instructions[instruction_idx],
+            offset_in_statement: code_offset - statement_debug_info.start_offset,
+        })
+    }
+
+    /// Maps the program counters in the trace to CASM instructions.
+    #[must_use]
+    pub fn map_trace(&self, vm_trace: &[TraceEntry]) -> Vec<InstructionMappingResult<'a>> {
+        vm_trace.iter().map(|step| self.map_pc(step.pc)).collect()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use cairo_lang_sierra::ProgramParser;
+    use cairo_lang_sierra_to_casm::compiler::{SierraToCasmConfig, compile};
+    use cairo_lang_sierra_to_casm::metadata::calc_metadata_ap_change_only;
+    use cairo_lang_sierra_type_size::ProgramRegistryInfo;
+
+    /// 0: `felt252_add` - no instructions, the addition is deferred
+    /// 1: `store_temp<felt252>` - one instruction, computing the addition
+    /// 2: `return`
+    const PROGRAM: &str = "
+        type felt252 = felt252;
+
+        libfunc felt252_add = felt252_add;
+        libfunc store_temp<felt252> = store_temp<felt252>;
+
+        felt252_add(a, b) -> (c);
+        store_temp<felt252>(c) -> (c);
+        return(c);
+
+        pkg::add@0(a: felt252, b: felt252) -> (felt252);
+    ";
+
+    /// Parses the Sierra program and compiles it to CASM without gas checks.
+    pub(crate) fn compile_program(sierra: &str) -> (Program, CairoProgram) {
+        let program = ProgramParser::new().parse(sierra).unwrap();
+        let program_registry_info = ProgramRegistryInfo::new(&program).unwrap();
+        let metadata = calc_metadata_ap_change_only(&program, &program_registry_info).unwrap();
+        let cairo_program = compile(
+            &program,
+            &program_registry_info,
+            &metadata,
+            SierraToCasmConfig {
+                gas_usage_check: false,
+                max_bytecode_size: usize::MAX,
+            },
+        )
+        .unwrap();
+        (program, cairo_program)
+    }
+
+    #[test]
+    fn test_map_pc() {
+        let (program, cairo_program) = compile_program(PROGRAM);
+        let casm_mapper = CasmMapper::new(&program, &cairo_program, 1);
+
+        let InstructionMappingResult::Instruction(store_temp) = casm_mapper.map_pc(1) else {
+            panic!("pc 1 not mapped to an instruction");
+        };
+        assert_eq!(store_temp.statement_idx, StatementIdx(1));
+        let libfunc = store_temp.libfunc.unwrap();
+        assert_eq!(libfunc.generic_name, "store_temp");
+        assert_eq!(libfunc.id, &program.libfunc_declarations[1].id);
+        assert_eq!(store_temp.instruction, &cairo_program.instructions[0]);
+        assert_eq!(
+            store_temp.instruction_text(),
+            "[ap + 0] = [fp + -4] + [fp + -3], ap++"
+        );
+        assert_eq!(store_temp.offset_in_statement, 0);
+
+        let InstructionMappingResult::Instruction(ret) = casm_mapper.map_pc(2) else {
+            panic!("pc 2 not mapped to an instruction");
+        };
+        assert_eq!(ret.statement_idx, StatementIdx(2));
+        assert_eq!(ret.libfunc, None);
+        assert_eq!(ret.instruction_text(), "ret");
+
+        assert_eq!(casm_mapper.map_pc(0), InstructionMappingResult::Header);
+        assert_eq!(
+            casm_mapper.map_pc(3),
+            InstructionMappingResult::PcOutOfFunctionArea
+        );
+    }
+
+    #[test]
+    fn test_map_trace() {
+        let (program, cairo_program) = compile_program(PROGRAM);
+        let casm_level_info = CasmLevelInfo {
+            run_with_call_header: false,
+            vm_trace: [1, 2].map(|pc| TraceEntry { pc, ap: 0, fp: 0 }).to_vec(),
+            program_offset: None,
+        };
+
+        let mapping_results = CasmMapper::for_trace(&program, &cairo_program, &casm_level_info)
+            .map_trace(&casm_level_info.vm_trace);
+
+        assert_eq!(
+            mapping_results
+                .iter()
+                .map(MappingResult::from)
+                .collect::<Vec<_>>(),
+            crate::map_pcs_to_sierra_statement_ids(&cairo_program.debug_info, &casm_level_info)
+        );
+    }
+}
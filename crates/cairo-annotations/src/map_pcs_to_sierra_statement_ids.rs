@@ -1,7 +1,8 @@
-use crate::trace_data::CasmLevelInfo;
+use crate::trace_data::{CasmLevelInfo, TraceEntry};
 use cairo_lang_sierra::program::StatementIdx;
 use cairo_lang_sierra_to_casm::compiler::{CairoProgramDebugInfo, SierraStatementDebugInfo};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Enum to represent the result of mapping a pc to a sierra statement id.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
@@ -26,21 +27,38 @@ impl From<MappingResult> for Option<StatementIdx> {
 /// Function to map the program counters in the trace to the sierra statement ids.
 ///
 /// Returns an empty vector if the sierra statement info is empty or the vm trace is empty.
+///
+/// If it is unknown which headers the runner added to the program, set `program_offset` to the one
+/// found with [`detect_program_offset`](crate::detect_program_offset).
 #[must_use]
 pub fn map_pcs_to_sierra_statement_ids(
     CairoProgramDebugInfo {
         sierra_statement_info,
     }: &CairoProgramDebugInfo,
-    CasmLevelInfo {
-        run_with_call_header,
-        vm_trace,
-        program_offset,
-    }: &CasmLevelInfo,
+    casm_level_info: &CasmLevelInfo,
 ) -> Vec<MappingResult> {
     if sierra_statement_info.is_empty() {
         return Vec::new();
     }
 
+    let real_minimal_pc = real_minimal_pc(casm_level_info);
+
+    casm_level_info
+        .vm_trace
+        .iter()
+        .map(|step| step.pc)
+        .map(|pc| map_pc_to_sierra_statement_id(sierra_statement_info, pc, real_minimal_pc))
+        .collect()
+}
+
+/// Returns the pc in the trace of the first CASM instruction of the original program.
+pub(crate) fn real_minimal_pc(
+    CasmLevelInfo {
+        run_with_call_header,
+        vm_trace,
+        program_offset,
+    }: &CasmLevelInfo,
+) -> usize {
     // Some CASM programs starts with a header(s) of instructions to wrap the real program.
     // `real_minimal_pc` is the PC in the trace that points to the same CASM instruction which would
     // be in the PC=1 in the original CASM program.
@@ -54,20 +72,47 @@ pub fn map_pcs_to_sierra_statement_ids(
     // which has two: one with `jump rel 0` and a second one which size needs to be included to
     // properly map pcs to statement ids.
     // In order to accommodate such cases, there's an option to set custom program offset here.
-    let real_minimal_pc = if let Some(offset) = program_offset {
+    if let Some(offset) = program_offset {
         offset + 1
     } else {
         run_with_call_header
             .then(|| vm_trace.last())
             .flatten()
             .map_or(1, |trace_entry| trace_entry.pc + 1)
-    };
+    }
+}
 
-    vm_trace
-        .iter()
-        .map(|step| step.pc)
-        .map(|pc| map_pc_to_sierra_statement_id(sierra_statement_info, pc, real_minimal_pc))
-        .collect()
+/// Returns the sierra statements in the order they were executed.
+///
+/// A statement usually consists of multiple CASM instructions, so consecutive results
+/// mapped to the same statement are collapsed into a single execution.
+/// Results that are not mapped to a sierra statement are skipped.
+pub fn statement_executions<'a>(
+    mapping_results: impl IntoIterator<Item = &'a MappingResult>,
+) -> impl Iterator<Item = StatementIdx> {
+    let mut previous = None;
+    mapping_results
+        .into_iter()
+        .filter_map(move |&mapping_result| {
+            let current = Option::<StatementIdx>::from(mapping_result);
+            let is_new_execution = current.is_some() && current != previous;
+            previous = current;
+            current.filter(|_| is_new_execution)
+        })
+}
+
+/// Counts how many times each sierra statement was executed.
+///
+/// See [`statement_executions`] for how executions are determined.
+#[must_use]
+pub fn count_statement_hits<'a>(
+    mapping_results: impl IntoIterator<Item = &'a MappingResult>,
+) -> HashMap<StatementIdx, usize> {
+    let mut statement_hits = HashMap::new();
+    for statement_idx in statement_executions(mapping_results) {
+        *statement_hits.entry(statement_idx).or_default() += 1;
+    }
+    statement_hits
 }
 
 /// Maps a program counter (pc) to a Sierra statement ID.
@@ -117,3 +162,157 @@ pub fn map_pc_to_sierra_statement_id(
         MappingResult::SierraStatementIdx(statement_index)
     }
 }
+
+/// Maps program counters to sierra statement ids in constant time, using a lookup table indexed
+/// by code offset.
+///
+/// Building the table takes time proportional to the bytecode length, so it pays off for long traces
+/// or when mapping multiple traces of the same program.
+/// Produces the same results as [`map_pc_to_sierra_statement_id`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PcMapper {
+    /// Index of the sierra statement of each code offset.
+    statements: Vec<u32>,
+    real_minimal_pc: usize,
+}
+
+impl PcMapper {
+    /// Number of trace entries mapped in a single task with the `rayon` feature.
+    #[cfg(feature = "rayon")]
+    const CHUNK_LEN: usize = 1 << 16;
+
+    /// Builds the lookup table for a program whose first CASM instruction is at `real_minimal_pc` in traces.
+    #[must_use]
+    pub fn new(debug_info: &CairoProgramDebugInfo, real_minimal_pc: usize) -> Self {
+        let sierra_statement_info = &debug_info.sierra_statement_info;
+        let bytecode_length = sierra_statement_info
+            .last()
+            .map_or(0, |statement_debug_info| statement_debug_info.end_offset);
+
+        // A code offset belongs to the last statement starting at or before it, so statements with
+        // no instructions are skipped in favour of the statement following them.
+        let mut statements = Vec::with_capacity(bytecode_length);
+        let mut previous = None;
+        for (statement_idx, statement_debug_info) in (0..).zip(sierra_statement_info) {
+            if let Some(previous) = previous {
+                statements.resize(statement_debug_info.start_offset, previous);
+            }
+            previous = Some(statement_idx);
+        }
+        if let Some(last) = previous {
+            statements.resize(bytecode_length, last);
+        }
+
+        Self {
+            statements,
+            real_minimal_pc,
+        }
+    }
+
+    /// Builds the lookup table for the program a trace was run with, taking headers into account
+    /// the same way as [`map_pcs_to_sierra_statement_ids`].
+    #[must_use]
+    pub fn for_trace(debug_info: &CairoProgramDebugInfo, casm_level_info: &CasmLevelInfo) -> Self {
+        Self::new(debug_info, real_minimal_pc(casm_level_info))
+    }
+
+    /// Maps a program counter to a sierra statement id.
+    #[must_use]
+    pub fn map_pc(&self, pc: usize) -> MappingResult {
+        let Some(code_offset) = pc.checked_sub(self.real_minimal_pc) else {
+            return MappingResult::Header;
+        };
+        match self.statements.get(code_offset) {
+            Some(&statement_idx) => {
+                MappingResult::SierraStatementIdx(StatementIdx(statement_idx as usize))
+            }
+            None => MappingResult::PcOutOfFunctionArea,
+        }
+    }
+
+    /// Maps the program counters in the trace to the sierra statement ids.
+    ///
+    /// With the `rayon` feature, chunks of the trace are mapped in parallel.
+    #[must_use]
+    pub fn map_trace(&self, vm_trace: &[TraceEntry]) -> Vec<MappingResult> {
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            vm_trace
+                .par_iter()
+                .with_min_len(Self::CHUNK_LEN)
+                .map(|step| self.map_pc(step.pc))
+                .collect()
+        }
+        #[cfg(not(feature = "rayon"))]
+        {
+            vm_trace.iter().map(|step| self.map_pc(step.pc)).collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cairo_lang_sierra_to_casm::compiler::{ReturnStatementDebugInfo, StatementKindDebugInfo};
+
+    /// Statement 1 has no instructions.
+    fn debug_info() -> CairoProgramDebugInfo {
+        let statement = |start_offset, end_offset| SierraStatementDebugInfo {
+            start_offset,
+            end_offset,
+            instruction_idx: 0,
+            additional_kind_info: StatementKindDebugInfo::Return(ReturnStatementDebugInfo {
+                ref_values: Vec::new(),
+            }),
+        };
+        CairoProgramDebugInfo {
+            sierra_statement_info: vec![statement(0, 2), statement(2, 2), statement(2, 5)],
+        }
+    }
+
+    #[test]
+    fn test_pc_mapper() {
+        let debug_info = debug_info();
+        let pc_mapper = PcMapper::new(&debug_info, 3);
+
+        for pc in 0..10 {
+            assert_eq!(
+                pc_mapper.map_pc(pc),
+                map_pc_to_sierra_statement_id(&debug_info.sierra_statement_info, pc, 3),
+                "pc {pc}"
+            );
+        }
+        assert_eq!(pc_mapper.map_pc(2), MappingResult::Header);
+        assert_eq!(
+            pc_mapper.map_pc(5),
+            MappingResult::SierraStatementIdx(StatementIdx(2))
+        );
+        assert_eq!(pc_mapper.map_pc(8), MappingResult::PcOutOfFunctionArea);
+    }
+
+    #[test]
+    fn test_pc_mapper_for_trace() {
+        let debug_info = debug_info();
+        let trace_entry = |pc| TraceEntry { pc, ap: 0, fp: 0 };
+        let casm_level_info = CasmLevelInfo {
+            run_with_call_header: true,
+            vm_trace: [1, 3, 4, 6, 8, 9, 2].map(trace_entry).to_vec(),
+            program_offset: None,
+        };
+
+        assert_eq!(
+            PcMapper::for_trace(&debug_info, &casm_level_info).map_trace(&casm_level_info.vm_trace),
+            map_pcs_to_sierra_statement_ids(&debug_info, &casm_level_info)
+        );
+        let empty_debug_info = CairoProgramDebugInfo {
+            sierra_statement_info: Vec::new(),
+        };
+        assert!(
+            PcMapper::new(&empty_debug_info, 1)
+                .map_trace(&casm_level_info.vm_trace)
+                .iter()
+                .all(|mapping_result| *mapping_result == MappingResult::PcOutOfFunctionArea)
+        );
+    }
+}
@@ -0,0 +1,416 @@
+use crate::MappingResult;
+use crate::annotations::attribution::AttributionPolicy;
+use crate::annotations::coverage::CoverageAnnotationsV1;
+use crate::annotations::profiler::{FunctionName, ProfilerAnnotationsV1};
+use crate::coverage_report::branches::taken_branch;
+use crate::statement_executions;
+use cairo_lang_casm::ap_change::ApChange;
+use cairo_lang_casm::cell_expression::{CellExpression, CellOperator};
+use cairo_lang_casm::operand::{CellRef, DerefOrImmediate, Register};
+use cairo_lang_sierra::ids::ConcreteTypeId;
+use cairo_lang_sierra::program::{Program, Statement, StatementIdx};
+use cairo_lang_sierra_to_casm::compiler::{CairoProgramDebugInfo, StatementKindDebugInfo};
+use cairo_lang_sierra_to_casm::references::ReferenceExpression;
+use std::collections::{BTreeMap, HashMap};
+use strum::VariantArray;
+use strum_macros::{Display, EnumString, VariantArray};
+
+/// Number of used instances of each builtin.
+pub type BuiltinCounter = BTreeMap<Builtin, usize>;
+
+/// Builtins whose usage can be attributed to sierra statements.
+///
+/// Displayed in `snake_case`, e.g. `range_check96`.
+#[derive(
+    Clone, Copy, Debug, Display, Eq, Hash, Ord, PartialEq, PartialOrd, EnumString, VariantArray,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum Builtin {
+    RangeCheck,
+    RangeCheck96,
+    Pedersen,
+    Poseidon,
+    Bitwise,
+    EcOp,
+    AddMod,
+    MulMod,
+}
+
+impl Builtin {
+    #[must_use]
+    pub fn all() -> &'static [Self] {
+        Self::VARIANTS
+    }
+
+    /// Name of the builtin used in
+    /// [`VmExecutionResources::builtin_instance_counter`](crate::trace_data::VmExecutionResources::builtin_instance_counter),
+    /// e.g. `range_check_builtin`.
+    #[must_use]
+    pub fn counter_name(self) -> String {
+        format!("{self}_builtin")
+    }
+
+    /// Finds the builtin by the name of its generic sierra type, e.g. `RangeCheck`.
+    #[must_use]
+    pub fn from_type_name(type_name: &str) -> Option<Self> {
+        match type_name {
+            "RangeCheck" => Some(Builtin::RangeCheck),
+            "RangeCheck96" => Some(Builtin::RangeCheck96),
+            "Pedersen" => Some(Builtin::Pedersen),
+            "Poseidon" => Some(Builtin::Poseidon),
+            "Bitwise" => Some(Builtin::Bitwise),
+            "EcOp" => Some(Builtin::EcOp),
+            "AddMod" => Some(Builtin::AddMod),
+            "MulMod" => Some(Builtin::MulMod),
+            _ => None,
+        }
+    }
+
+    /// Number of memory cells occupied by a single instance of the builtin.
+    #[must_use]
+    pub fn instance_size(self) -> usize {
+        match self {
+            Builtin::RangeCheck | Builtin::RangeCheck96 => 1,
+            Builtin::Pedersen => 3,
+            Builtin::Bitwise => 5,
+            Builtin::Poseidon => 6,
+            Builtin::EcOp | Builtin::AddMod | Builtin::MulMod => 7,
+        }
+    }
+}
+
+/// Builtin instances used by each branch of sierra statements, derived from the compiled program.
+///
+/// A libfunc using a builtin returns the builtin pointer advanced by the number of cells it used.
+/// Pointers which are not advanced by a constant, e.g. the ones returned from function calls,
+/// are not taken into account, so the usage is attributed only to statements that use the builtin directly.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BuiltinCosts {
+    /// Builtins used by each branch of statements using any builtin, indexed the same as libfunc branches.
+    pub statements: HashMap<StatementIdx, Vec<BuiltinCounter>>,
+}
+
+/// Builtin usage attributed to sierra statements.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BuiltinUsage {
+    pub statements: BTreeMap<StatementIdx, BuiltinCounter>,
+}
+
+impl BuiltinCosts {
+    /// Analyses which statements use builtins, based on the references of builtin pointers
+    /// before and after each statement.
+    ///
+    /// `debug_info` must come from compiling `program` with
+    /// [`compile`](cairo_lang_sierra_to_casm::compiler::compile).
+    #[must_use]
+    pub fn new(program: &Program, debug_info: &CairoProgramDebugInfo) -> Self {
+        let builtin_types: HashMap<&ConcreteTypeId, Builtin> = program
+            .type_declarations
+            .iter()
+            .filter_map(|declaration| {
+                Builtin::from_type_name(declaration.long_id.generic_id.0.as_str())
+                    .map(|builtin| (&declaration.id, builtin))
+            })
+            .collect();
+
+        let statements = debug_info
+            .sierra_statement_info
+            .iter()
+            .enumerate()
+            .filter_map(|(index, statement_debug_info)| {
+                let StatementKindDebugInfo::Invoke(invoke_debug_info) =
+                    &statement_debug_info.additional_kind_info
+                else {
+                    return None;
+                };
+
+                let branches: Vec<BuiltinCounter> = invoke_debug_info
+                    .result_branch_changes
+                    .iter()
+                    .map(|branch_changes| {
+                        let mut builtins = BuiltinCounter::new();
+                        for output in &branch_changes.refs {
+                            let Some(&builtin) = builtin_types.get(&output.ty) else {
+                                continue;
+                            };
+                            let used_cells = invoke_debug_info
+                                .ref_values
+                                .iter()
+                                .filter(|input| input.ty == output.ty)
+                                .find_map(|input| {
+                                    pointer_advance(
+                                        &input.expression,
+                                        &output.expression,
+                                        branch_changes.ap_change,
+                                    )
+                                });
+                            if let Some(used_cells) = used_cells.filter(|&cells| cells > 0) {
+                                *builtins.entry(builtin).or_default() +=
+                                    used_cells / builtin.instance_size();
+                            }
+                        }
+                        builtins
+                    })
+                    .collect();
+
+                branches
+                    .iter()
+                    .any(|builtins| !builtins.is_empty())
+                    .then_some((StatementIdx(index), branches))
+            })
+            .collect();
+
+        Self { statements }
+    }
+}
+
+impl BuiltinUsage {
+    /// Records builtins used in a trace mapped with
+    /// [`map_pcs_to_sierra_statement_ids`](crate::map_pcs_to_sierra_statement_ids).
+    ///
+    /// The branch taken by a statement is determined from the next executed statement.
+    /// If it cannot be determined, e.g. for the last statement of the trace, only the builtins
+    /// used by all the branches are recorded.
+    pub fn record_trace(
+        &mut self,
+        program: &Program,
+        builtin_costs: &BuiltinCosts,
+        mapping_results: &[MappingResult],
+    ) {
+        let executions: Vec<_> = statement_executions(mapping_results).collect();
+
+        for (index, statement_idx) in executions.iter().enumerate() {
+            let Some(branches) = builtin_costs.statements.get(statement_idx) else {
+                continue;
+            };
+
+            let targets: Vec<_> = match program.statements.get(statement_idx.0) {
+                Some(Statement::Invocation(invocation)) => invocation
+                    .branches
+                    .iter()
+                    .map(|branch| statement_idx.next(branch.target))
+                    .collect(),
+                _ => Vec::new(),
+            };
+            let branch = match targets.as_slice() {
+                [_] => Some(0),
+                _ => executions.get(index + 1).and_then(|&next_statement_idx| {
+                    taken_branch(program, &targets, next_statement_idx)
+                }),
+            };
+            let used_builtins = branch
+                .and_then(|branch| branches.get(branch).cloned())
+                .unwrap_or_else(|| used_by_all_branches(branches));
+
+            let statement_usage = self.statements.entry(*statement_idx).or_default();
+            for (builtin, count) in used_builtins {
+                *statement_usage.entry(builtin).or_default() += count;
+            }
+        }
+    }
+
+    /// Total number of used instances of each builtin.
+    #[must_use]
+    pub fn total(&self) -> BuiltinCounter {
+        let mut total = BuiltinCounter::new();
+        for (builtin, count) in self.statements.values().flatten() {
+            *total.entry(*builtin).or_default() += count;
+        }
+        total
+    }
+
+    /// Attributes the usage to the Cairo functions that generated the statements, selected by `policy`.
+    /// See [`ProfilerAnnotationsV1::resolve_functions`].
+    ///
+    /// Statements missing from profiler annotations are skipped.
+    #[must_use]
+    pub fn by_function<'a>(
+        &self,
+        annotations: &'a ProfilerAnnotationsV1,
+        policy: AttributionPolicy,
+        coverage_annotations: Option<&CoverageAnnotationsV1>,
+    ) -> BTreeMap<&'a FunctionName, BuiltinCounter> {
+        let mut functions: BTreeMap<&FunctionName, BuiltinCounter> = BTreeMap::new();
+        for (statement_idx, builtins) in &self.statements {
+            for function_name in
+                annotations.resolve_functions(*statement_idx, policy, coverage_annotations)
+            {
+                let function_usage = functions.entry(function_name).or_default();
+                for (builtin, count) in builtins {
+                    *function_usage.entry(*builtin).or_default() += count;
+                }
+            }
+        }
+        functions
+    }
+}
+
+/// Number of cells the builtin pointer was advanced by, if it is the `input` pointer plus a constant.
+fn pointer_advance(
+    input: &ReferenceExpression,
+    output: &ReferenceExpression,
+    ap_change: ApChange,
+) -> Option<usize> {
+    let (input_cell, input_offset) = pointer_parts(input)?;
+    let (mut output_cell, output_offset) = pointer_parts(output)?;
+
+    // Output references are relative to `ap` after the statement.
+    if output_cell.register == Register::AP {
+        let ApChange::Known(ap_change) = ap_change else {
+            return None;
+        };
+        output_cell.offset = output_cell
+            .offset
+            .checked_add(i16::try_from(ap_change).ok()?)?;
+    }
+
+    (input_cell == output_cell)
+        .then(|| usize::try_from(output_offset - input_offset).ok())
+        .flatten()
+}
+
+/// Splits a pointer of the form `[cell]` or `[cell] + offset` into its parts.
+fn pointer_parts(expression: &ReferenceExpression) -> Option<(CellRef, i64)> {
+    match expression.cells.as_slice() {
+        [CellExpression::Deref(cell)] => Some((*cell, 0)),
+        [
+            CellExpression::BinOp {
+                op: CellOperator::Add,
+                a,
+                b: DerefOrImmediate::Immediate(offset),
+            },
+        ] => Some((*a, i64::try_from(&offset.value).ok()?)),
+        _ => None,
+    }
+}
+
+/// Builtins used regardless of the branch taken.
+fn used_by_all_branches(branches: &[BuiltinCounter]) -> BuiltinCounter {
+    Builtin::all()
+        .iter()
+        .filter_map(|builtin| {
+            let count = branches
+                .iter()
+                .map(|builtins| builtins.get(builtin).copied().unwrap_or_default())
+                .min()?;
+            (count > 0).then_some((*builtin, count))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::map_pcs_to_casm_instructions::test::compile_program;
+    use crate::profiling::test::profiler_annotations;
+
+    /// 0: `pedersen`
+    /// 2: `u8_overflowing_add` - to 3 or 8
+    /// 3-7 and 8-12: returning the builtins
+    const PROGRAM: &str = "
+        type RangeCheck = RangeCheck;
+        type Pedersen = Pedersen;
+        type felt252 = felt252;
+        type u8 = u8;
+
+        libfunc pedersen = pedersen;
+        libfunc u8_overflowing_add = u8_overflowing_add;
+        libfunc branch_align = branch_align;
+        libfunc drop<felt252> = drop<felt252>;
+        libfunc drop<u8> = drop<u8>;
+        libfunc store_temp<RangeCheck> = store_temp<RangeCheck>;
+        libfunc store_temp<Pedersen> = store_temp<Pedersen>;
+
+        pedersen(p, a, b) -> (p, h);
+        drop<felt252>(h) -> ();
+        u8_overflowing_add(rc, x, y) { fallthrough(rc, z) 8(rc, z) };
+        branch_align() -> ();
+        drop<u8>(z) -> ();
+        store_temp<RangeCheck>(rc) -> (rc);
+        store_temp<Pedersen>(p) -> (p);
+        return(rc, p);
+        branch_align() -> ();
+        drop<u8>(z) -> ();
+        store_temp<RangeCheck>(rc) -> (rc);
+        store_temp<Pedersen>(p) -> (p);
+        return(rc, p);
+
+        test_program@0(rc: RangeCheck, p: Pedersen, a: felt252, b: felt252, x: u8, y: u8) -> (RangeCheck, Pedersen);
+    ";
+
+    fn compiled_program() -> (Program, CairoProgramDebugInfo) {
+        let (program, cairo_program) = compile_program(PROGRAM);
+        (program, cairo_program.debug_info)
+    }
+
+    #[test]
+    fn test_builtin_costs() {
+        let (program, debug_info) = compiled_program();
+
+        let builtin_costs = BuiltinCosts::new(&program, &debug_info);
+
+        assert_eq!(
+            builtin_costs.statements,
+            HashMap::from([
+                (
+                    StatementIdx(0),
+                    vec![BuiltinCounter::from([(Builtin::Pedersen, 1)])]
+                ),
+                (
+                    StatementIdx(2),
+                    vec![
+                        BuiltinCounter::from([(Builtin::RangeCheck, 1)]),
+                        BuiltinCounter::from([(Builtin::RangeCheck, 1)]),
+                    ]
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_record_trace() {
+        let (program, debug_info) = compiled_program();
+        let builtin_costs = BuiltinCosts::new(&program, &debug_info);
+        let statement = |statement| MappingResult::SierraStatementIdx(StatementIdx(statement));
+
+        let mut builtin_usage = BuiltinUsage::default();
+        builtin_usage.record_trace(
+            &program,
+            &builtin_costs,
+            &[statement(0), statement(0), statement(2), statement(5)],
+        );
+        // The trace ends right after the branching statement.
+        builtin_usage.record_trace(&program, &builtin_costs, &[statement(2)]);
+
+        assert_eq!(
+            builtin_usage.total(),
+            BuiltinCounter::from([(Builtin::RangeCheck, 2), (Builtin::Pedersen, 1)])
+        );
+
+        let annotations = profiler_annotations([
+            (0, vec!["core::pedersen::pedersen", "pkg::hash"]),
+            (2, vec!["pkg::add"]),
+        ]);
+        assert_eq!(
+            builtin_usage.by_function(&annotations, AttributionPolicy::OutermostUserCode, None),
+            BTreeMap::from([
+                (
+                    &FunctionName("pkg::hash".to_string()),
+                    BuiltinCounter::from([(Builtin::Pedersen, 1)])
+                ),
+                (
+                    &FunctionName("pkg::add".to_string()),
+                    BuiltinCounter::from([(Builtin::RangeCheck, 2)])
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_builtin_names() {
+        assert_eq!(Builtin::RangeCheck96.to_string(), "range_check96");
+        assert_eq!(Builtin::EcOp.counter_name(), "ec_op_builtin");
+        assert_eq!(Builtin::from_type_name("Poseidon"), Some(Builtin::Poseidon));
+        assert_eq!(Builtin::from_type_name("felt252"), None);
+    }
+}
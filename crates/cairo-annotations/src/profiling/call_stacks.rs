@@ -0,0 +1,105 @@
+use crate::annotations::profiler::{FunctionName, ProfilerAnnotationsV1};
+use cairo_lang_sierra::program::StatementIdx;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Tracks call stacks of non-inlined functions while walking a trace.
+///
+/// Calls and returns are detected from changes of the frame pointer, inlined functions are taken
+/// from profiler annotations. Call stacks are interned, so they can be referred to by ids.
+pub(crate) struct CallStacks<'a> {
+    annotations: &'a ProfilerAnnotationsV1,
+    /// Frame reported for statements missing from `annotations`.
+    unknown_function: &'a FunctionName,
+    interned: Vec<Vec<&'a FunctionName>>,
+    interned_ids: HashMap<Vec<&'a FunctionName>, usize>,
+    /// Frame pointers of the callers and ids of their call stacks.
+    callers: Vec<(usize, usize)>,
+    call_stack_id: usize,
+    previous: Option<(usize, Option<StatementIdx>)>,
+}
+
+impl<'a> CallStacks<'a> {
+    pub(crate) fn new(
+        annotations: &'a ProfilerAnnotationsV1,
+        unknown_function: &'a FunctionName,
+    ) -> Self {
+        Self {
+            annotations,
+            unknown_function,
+            interned: vec![Vec::new()],
+            interned_ids: HashMap::from([(vec![], 0)]),
+            callers: Vec::new(),
+            call_stack_id: 0,
+            previous: None,
+        }
+    }
+
+    /// Frames of a statement from the outermost to the innermost.
+    pub(crate) fn statement_frames(&self, statement_idx: StatementIdx) -> Vec<&'a FunctionName> {
+        self.annotations
+            .statements_functions
+            .get(&statement_idx)
+            .filter(|function_names| !function_names.is_empty())
+            .map_or_else(
+                || vec![self.unknown_function],
+                |function_names| function_names.iter().rev().collect(),
+            )
+    }
+
+    /// Advances to the next entry of the trace, executed with frame pointer `fp`.
+    ///
+    /// Returns the id of the call stack of non-inlined functions the entry is executed with,
+    /// and whether the entry starts a new execution of the statement.
+    pub(crate) fn step(&mut self, fp: usize, statement_idx: Option<StatementIdx>) -> (usize, bool) {
+        let mut new_execution = true;
+
+        if let Some((previous_fp, previous_statement_idx)) = self.previous {
+            match fp.cmp(&previous_fp) {
+                Ordering::Greater => {
+                    let mut call_stack = self.interned[self.call_stack_id].clone();
+                    call_stack.extend(
+                        previous_statement_idx
+                            .map(|statement_idx| self.statement_frames(statement_idx))
+                            .unwrap_or_default(),
+                    );
+                    self.callers.push((previous_fp, self.call_stack_id));
+                    self.call_stack_id = *self
+                        .interned_ids
+                        .entry(call_stack.clone())
+                        .or_insert_with(|| {
+                            self.interned.push(call_stack);
+                            self.interned.len() - 1
+                        });
+                }
+                Ordering::Less => {
+                    while let Some(&(caller_fp, caller_call_stack_id)) = self.callers.last() {
+                        if caller_fp < fp {
+                            break;
+                        }
+                        self.callers.pop();
+                        self.call_stack_id = caller_call_stack_id;
+                    }
+                }
+                Ordering::Equal => {
+                    new_execution = statement_idx != previous_statement_idx;
+                }
+            }
+        }
+        self.previous = Some((fp, statement_idx));
+
+        (self.call_stack_id, new_execution)
+    }
+
+    /// Full stack of a statement executed with the call stack `call_stack_id`,
+    /// from the outermost to the innermost frame.
+    pub(crate) fn frames(
+        &self,
+        call_stack_id: usize,
+        statement_idx: StatementIdx,
+    ) -> Vec<&'a FunctionName> {
+        let mut frames = self.interned[call_stack_id].clone();
+        frames.extend(self.statement_frames(statement_idx));
+        frames
+    }
+}
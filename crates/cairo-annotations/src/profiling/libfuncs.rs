@@ -0,0 +1,352 @@
+use crate::map_pcs_to_casm_instructions::InstructionMappingResult;
+use crate::map_pcs_to_sierra_statement_ids::MappingResult;
+use cairo_lang_casm::instructions::{AddApInstruction, Instruction, InstructionBody};
+use cairo_lang_casm::operand::ResOperand;
+use cairo_lang_sierra::ids::ConcreteLibfuncId;
+use cairo_lang_sierra::program::{Program, Statement, StatementIdx};
+use std::collections::{BTreeMap, HashMap};
+
+/// Name under which steps of `return` statements are reported, as they do not invoke a libfunc.
+pub const RETURN: &str = "return";
+/// Name under which steps of statements missing from the program, or invoking undeclared libfuncs, are reported.
+pub const UNKNOWN_LIBFUNC: &str = "<unknown>";
+
+/// Steps spent in libfuncs, aggregated by generic and concrete libfunc,
+/// e.g. `array_append` and `array_append<felt252>`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LibfuncHistogram {
+    /// Entries by name of the generic libfunc.
+    pub generic: BTreeMap<String, LibfuncCost>,
+    /// Entries by name of the concrete libfunc. Libfuncs without debug names are reported by their id, e.g. `[3]`.
+    pub concrete: BTreeMap<String, LibfuncCost>,
+    /// Cost of all recorded statements.
+    pub total: LibfuncCost,
+    /// Whether memory holes were recorded for any of the recorded traces.
+    pub memory_holes_recorded: bool,
+}
+
+/// Cost of a single libfunc in a [`LibfuncHistogram`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct LibfuncCost {
+    pub steps: usize,
+    /// Memory cells skipped by `ap += <constant>` instructions, e.g. emitted by `branch_align`
+    /// to equalize the ap change of branches.
+    pub memory_holes: usize,
+}
+
+/// Level of aggregation of a [`LibfuncHistogram`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum LibfuncLevel {
+    #[default]
+    Generic,
+    Concrete,
+}
+
+/// Cost by which [`LibfuncHistogram`] entries are weighted.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum HistogramWeight {
+    #[default]
+    Steps,
+    /// Steps and memory holes together, as memory holes are charged like steps on Starknet.
+    StepsAndMemoryHoles,
+}
+
+impl LibfuncHistogram {
+    /// Records steps of a trace mapped with [`map_pcs_to_sierra_statement_ids`](crate::map_pcs_to_sierra_statement_ids).
+    ///
+    /// `program` must be the program the trace was run with. Memory holes are not recorded,
+    /// use [`LibfuncHistogram::record_instructions`] for that.
+    pub fn record_trace(&mut self, program: &Program, mapping_results: &[MappingResult]) {
+        let generic_names: HashMap<&ConcreteLibfuncId, &str> = program
+            .libfunc_declarations
+            .iter()
+            .map(|declaration| (&declaration.id, declaration.long_id.generic_id.0.as_str()))
+            .collect();
+
+        let mut statement_steps: HashMap<usize, usize> = HashMap::new();
+        for statement_idx in mapping_results
+            .iter()
+            .filter_map(|&mapping_result| Option::<StatementIdx>::from(mapping_result))
+        {
+            *statement_steps.entry(statement_idx.0).or_default() += 1;
+        }
+
+        for (statement_idx, steps) in statement_steps {
+            let (generic_name, concrete_name) = match program.statements.get(statement_idx) {
+                Some(Statement::Invocation(invocation)) => (
+                    generic_names
+                        .get(&invocation.libfunc_id)
+                        .copied()
+                        .unwrap_or(UNKNOWN_LIBFUNC),
+                    invocation.libfunc_id.to_string(),
+                ),
+                Some(Statement::Return(_)) => (RETURN, RETURN.to_string()),
+                None => (UNKNOWN_LIBFUNC, UNKNOWN_LIBFUNC.to_string()),
+            };
+            self.add(
+                generic_name,
+                concrete_name,
+                LibfuncCost {
+                    steps,
+                    memory_holes: 0,
+                },
+            );
+        }
+    }
+
+    /// Records steps and memory holes of a trace mapped with [`CasmMapper`](crate::CasmMapper).
+    pub fn record_instructions(&mut self, mapping_results: &[InstructionMappingResult<'_>]) {
+        let mut libfunc_costs: HashMap<(Option<&ConcreteLibfuncId>, &str), LibfuncCost> =
+            HashMap::new();
+        for mapping_result in mapping_results {
+            let InstructionMappingResult::Instruction(mapped_instruction) = mapping_result else {
+                continue;
+            };
+            let key = match mapped_instruction.libfunc {
+                Some(libfunc) => (Some(libfunc.id), libfunc.generic_name),
+                None => (None, RETURN),
+            };
+            let cost = libfunc_costs.entry(key).or_default();
+            cost.steps += 1;
+            cost.memory_holes += memory_holes(mapped_instruction.instruction);
+        }
+
+        self.memory_holes_recorded = true;
+        for ((libfunc_id, generic_name), cost) in libfunc_costs {
+            let concrete_name = libfunc_id.map_or_else(|| RETURN.to_string(), ToString::to_string);
+            self.add(generic_name, concrete_name, cost);
+        }
+    }
+
+    fn add(&mut self, generic_name: &str, concrete_name: String, cost: LibfuncCost) {
+        for (entries, name) in [
+            (&mut self.generic, generic_name.to_string()),
+            (&mut self.concrete, concrete_name),
+        ] {
+            entries.entry(name).or_default().add(cost);
+        }
+        self.total.add(cost);
+    }
+
+    /// Returns entries aggregated at `level`.
+    #[must_use]
+    pub fn entries(&self, level: LibfuncLevel) -> &BTreeMap<String, LibfuncCost> {
+        match level {
+            LibfuncLevel::Generic => &self.generic,
+            LibfuncLevel::Concrete => &self.concrete,
+        }
+    }
+
+    /// Returns entries aggregated at `level`, sorted by `weight` in descending order, and then by name.
+    /// At most `limit` entries are returned, if provided.
+    #[must_use]
+    pub fn top(
+        &self,
+        level: LibfuncLevel,
+        weight: HistogramWeight,
+        limit: Option<usize>,
+    ) -> Vec<(&str, &LibfuncCost)> {
+        let mut entries: Vec<_> = self
+            .entries(level)
+            .iter()
+            .map(|(name, cost)| (name.as_str(), cost))
+            .collect();
+        entries.sort_by(|(name_a, cost_a), (name_b, cost_b)| {
+            cost_b
+                .weight(weight)
+                .cmp(&cost_a.weight(weight))
+                .then_with(|| name_a.cmp(name_b))
+        });
+        entries.truncate(limit.unwrap_or(entries.len()));
+        entries
+    }
+}
+
+impl LibfuncCost {
+    /// Returns the cost weighted by `weight`.
+    #[must_use]
+    pub fn weight(&self, weight: HistogramWeight) -> usize {
+        match weight {
+            HistogramWeight::Steps => self.steps,
+            HistogramWeight::StepsAndMemoryHoles => self.steps + self.memory_holes,
+        }
+    }
+
+    fn add(&mut self, other: LibfuncCost) {
+        self.steps += other.steps;
+        self.memory_holes += other.memory_holes;
+    }
+}
+
+/// Number of memory cells skipped by the instruction without being written.
+fn memory_holes(instruction: &Instruction) -> usize {
+    match &instruction.body {
+        InstructionBody::AddAp(AddApInstruction {
+            operand: ResOperand::Immediate(value),
+        }) => usize::try_from(&value.value).unwrap_or_default(),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::CasmMapper;
+    use crate::map_pcs_to_casm_instructions::test::compile_program;
+    use crate::trace_data::TraceEntry;
+    use cairo_lang_sierra::ProgramParser;
+
+    /// The fallthrough branch of `felt252_is_zero` stores one more temporary value than the other one,
+    /// so `branch_align` of the other branch skips a memory cell to equalize the ap change of the function.
+    const PROGRAM: &str = "
+        type felt252 = felt252;
+        type NonZeroFelt252 = NonZero<felt252>;
+
+        libfunc felt252_is_zero = felt252_is_zero;
+        libfunc branch_align = branch_align;
+        libfunc drop<NonZeroFelt252> = drop<NonZeroFelt252>;
+        libfunc felt252_const<1> = felt252_const<1>;
+        libfunc store_temp<felt252> = store_temp<felt252>;
+
+        felt252_is_zero(a) { fallthrough() 6(a) };
+        branch_align() -> ();
+        felt252_const<1>() -> (b);
+        store_temp<felt252>(b) -> (b);
+        store_temp<felt252>(b) -> (b);
+        return(b);
+        branch_align() -> ();
+        drop<NonZeroFelt252>(a) -> ();
+        felt252_const<1>() -> (b);
+        store_temp<felt252>(b) -> (b);
+        return(b);
+
+        pkg::main@0(a: felt252) -> (felt252);
+    ";
+
+    #[test]
+    fn test_record_trace() {
+        let program = ProgramParser::new().parse(PROGRAM).unwrap();
+        let statement = |statement| MappingResult::SierraStatementIdx(StatementIdx(statement));
+        let mapping_results = [
+            MappingResult::Header,
+            statement(0),
+            statement(0),
+            statement(3),
+            statement(5),
+            statement(0),
+            statement(9),
+            statement(10),
+        ];
+
+        let mut histogram = LibfuncHistogram::default();
+        histogram.record_trace(&program, &mapping_results);
+
+        assert!(!histogram.memory_holes_recorded);
+        assert_eq!(histogram.total.steps, 7);
+        assert_eq!(
+            histogram.generic,
+            BTreeMap::from([
+                (
+                    "felt252_is_zero".to_string(),
+                    LibfuncCost {
+                        steps: 3,
+                        memory_holes: 0
+                    }
+                ),
+                (
+                    "store_temp".to_string(),
+                    LibfuncCost {
+                        steps: 2,
+                        memory_holes: 0
+                    }
+                ),
+                (
+                    RETURN.to_string(),
+                    LibfuncCost {
+                        steps: 2,
+                        memory_holes: 0
+                    }
+                ),
+            ])
+        );
+        assert_eq!(histogram.concrete["store_temp<felt252>"].steps, 2);
+    }
+
+    #[test]
+    fn test_record_trace_unknown_statements() {
+        let mut program = ProgramParser::new().parse(PROGRAM).unwrap();
+        program
+            .libfunc_declarations
+            .retain(|declaration| declaration.id.to_string() != "branch_align");
+        let statement = |statement| MappingResult::SierraStatementIdx(StatementIdx(statement));
+
+        let mut histogram = LibfuncHistogram::default();
+        histogram.record_trace(&program, &[statement(1), statement(5), statement(100)]);
+
+        assert_eq!(histogram.generic[UNKNOWN_LIBFUNC].steps, 2);
+        assert_eq!(histogram.generic[RETURN].steps, 1);
+        assert_eq!(histogram.concrete["branch_align"].steps, 1);
+        assert_eq!(histogram.concrete[UNKNOWN_LIBFUNC].steps, 1);
+        assert_eq!(histogram.concrete[RETURN].steps, 1);
+    }
+
+    #[test]
+    fn test_record_instructions() {
+        let (program, cairo_program) = compile_program(PROGRAM);
+        let casm_mapper = CasmMapper::new(&program, &cairo_program, 0);
+        // Every instruction of both branches executed once.
+        let vm_trace: Vec<TraceEntry> = cairo_program
+            .instructions
+            .iter()
+            .scan(0, |pc, instruction| {
+                let entry = TraceEntry {
+                    pc: *pc,
+                    ap: 0,
+                    fp: 0,
+                };
+                *pc += instruction.body.op_size();
+                Some(entry)
+            })
+            .collect();
+
+        let mut histogram = LibfuncHistogram::default();
+        histogram.record_instructions(&casm_mapper.map_trace(&vm_trace));
+
+        assert!(histogram.memory_holes_recorded);
+        assert_eq!(histogram.total.memory_holes, 1);
+        assert_eq!(histogram.generic["branch_align"].memory_holes, 1);
+        assert_eq!(histogram.concrete["branch_align"].memory_holes, 1);
+        assert_eq!(histogram.generic[RETURN].steps, 2);
+        assert_eq!(histogram.total.steps, cairo_program.instructions.len(),);
+    }
+
+    #[test]
+    fn test_top() {
+        let mut histogram = LibfuncHistogram::default();
+        let cost = |steps, memory_holes| LibfuncCost {
+            steps,
+            memory_holes,
+        };
+        histogram.generic = BTreeMap::from([
+            ("branch_align".to_string(), cost(1, 5)),
+            ("felt252_add".to_string(), cost(4, 0)),
+            ("store_temp".to_string(), cost(4, 0)),
+        ]);
+
+        let names = |weight, limit| {
+            histogram
+                .top(LibfuncLevel::Generic, weight, limit)
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(HistogramWeight::Steps, None),
+            vec!["felt252_add", "store_temp", "branch_align"]
+        );
+        assert_eq!(
+            names(HistogramWeight::StepsAndMemoryHoles, Some(2)),
+            vec!["branch_align", "felt252_add"]
+        );
+    }
+}
@@ -0,0 +1,386 @@
+use crate::MappingResult;
+use crate::annotations::profiler::{FunctionName, ProfilerAnnotationsV1};
+use crate::profiling::call_stacks::CallStacks;
+use crate::trace_data::TraceEntry;
+use cairo_lang_sierra::program::StatementIdx;
+use std::collections::{BTreeMap, HashMap};
+
+pub mod builtins;
+mod call_stacks;
+pub mod libfuncs;
+pub mod syscalls;
+pub mod table;
+
+/// Name under which steps of statements missing from profiler annotations are reported.
+pub const UNKNOWN_FUNCTION: &str = "<unknown>";
+
+/// Steps and gas spent in Cairo functions, aggregated per function, module and crate.
+///
+/// Self cost of an entry is the cost of statements generated directly by it, i.e. statements whose innermost
+/// frame belongs to it. Total cost also includes the cost of everything it called or inlined.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Profile {
+    /// Entries by fully qualified function name.
+    pub functions: BTreeMap<String, ProfileEntry>,
+    /// Entries by module path, e.g. `core::array`.
+    pub modules: BTreeMap<String, ProfileEntry>,
+    /// Entries by crate name, e.g. `core`.
+    pub crates: BTreeMap<String, ProfileEntry>,
+    /// Number of steps mapped to sierra statements in all recorded traces.
+    pub total_steps: usize,
+    /// Gas consumed by sierra statements in all recorded traces.
+    pub total_gas: u64,
+    /// Whether statement gas costs were provided for any of the recorded traces.
+    pub gas_recorded: bool,
+}
+
+/// Cost of a single function, module or crate in a [`Profile`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ProfileEntry {
+    pub self_steps: usize,
+    pub total_steps: usize,
+    pub self_gas: u64,
+    pub total_gas: u64,
+}
+
+/// Level of aggregation of a [`Profile`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum ProfileLevel {
+    #[default]
+    Function,
+    Module,
+    Crate,
+}
+
+/// Cost by which [`Profile`] entries are sorted, in descending order.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum SortKey {
+    SelfSteps,
+    #[default]
+    TotalSteps,
+    SelfGas,
+    TotalGas,
+}
+
+/// Steps and gas of statements executed with the same call stack.
+#[derive(Clone, Copy, Debug, Default)]
+struct Sample {
+    steps: usize,
+    gas: u64,
+}
+
+impl Profile {
+    /// Records a trace mapped with [`map_pcs_to_sierra_statement_ids`](crate::map_pcs_to_sierra_statement_ids).
+    ///
+    /// `vm_trace` must be the trace the `mapping_results` were created from. Calls of non-inlined functions
+    /// are detected from changes of the frame pointer, inlined functions are taken from `annotations`.
+    ///
+    /// `statement_gas` is the gas cost of a single execution of each statement, e.g. computed with
+    /// `cairo-lang-sierra-gas`. If not provided, only steps are recorded.
+    pub fn record_trace(
+        &mut self,
+        annotations: &ProfilerAnnotationsV1,
+        vm_trace: &[TraceEntry],
+        mapping_results: &[MappingResult],
+        statement_gas: Option<&HashMap<StatementIdx, u64>>,
+    ) {
+        let unknown_function = FunctionName(UNKNOWN_FUNCTION.to_string());
+        let mut call_stacks = CallStacks::new(annotations, &unknown_function);
+        let mut samples: HashMap<(usize, StatementIdx), Sample> = HashMap::new();
+
+        for (trace_entry, &mapping_result) in vm_trace.iter().zip(mapping_results) {
+            let statement_idx = Option::<StatementIdx>::from(mapping_result);
+            let (call_stack_id, new_execution) = call_stacks.step(trace_entry.fp, statement_idx);
+
+            let Some(statement_idx) = statement_idx else {
+                continue;
+            };
+            let sample = samples.entry((call_stack_id, statement_idx)).or_default();
+            sample.steps += 1;
+            if new_execution && let Some(statement_gas) = statement_gas {
+                sample.gas += statement_gas
+                    .get(&statement_idx)
+                    .copied()
+                    .unwrap_or_default();
+            }
+        }
+
+        self.gas_recorded |= statement_gas.is_some();
+        for ((call_stack_id, statement_idx), sample) in samples {
+            self.add_sample(&call_stacks.frames(call_stack_id, statement_idx), sample);
+        }
+    }
+
+    /// Adds a sample executed with the call stack `frames`, ordered from the outermost to the innermost.
+    fn add_sample(&mut self, frames: &[&FunctionName], sample: Sample) {
+        self.total_steps += sample.steps;
+        self.total_gas += sample.gas;
+
+        for level in [
+            ProfileLevel::Function,
+            ProfileLevel::Module,
+            ProfileLevel::Crate,
+        ] {
+            let mut keys: Vec<String> = frames.iter().map(|frame| level.key(frame)).collect();
+            let innermost = keys.last().cloned();
+            keys.sort();
+            keys.dedup();
+
+            let entries = self.entries_mut(level);
+            for key in keys {
+                let entry = entries.entry(key).or_default();
+                entry.total_steps += sample.steps;
+                entry.total_gas += sample.gas;
+            }
+            if let Some(innermost) = innermost {
+                let entry = entries.entry(innermost).or_default();
+                entry.self_steps += sample.steps;
+                entry.self_gas += sample.gas;
+            }
+        }
+    }
+
+    /// Returns entries aggregated at `level`.
+    #[must_use]
+    pub fn entries(&self, level: ProfileLevel) -> &BTreeMap<String, ProfileEntry> {
+        match level {
+            ProfileLevel::Function => &self.functions,
+            ProfileLevel::Module => &self.modules,
+            ProfileLevel::Crate => &self.crates,
+        }
+    }
+
+    fn entries_mut(&mut self, level: ProfileLevel) -> &mut BTreeMap<String, ProfileEntry> {
+        match level {
+            ProfileLevel::Function => &mut self.functions,
+            ProfileLevel::Module => &mut self.modules,
+            ProfileLevel::Crate => &mut self.crates,
+        }
+    }
+
+    /// Returns entries aggregated at `level`, sorted by `sort_key` in descending order, and then by name.
+    /// At most `limit` entries are returned, if provided.
+    #[must_use]
+    pub fn top(
+        &self,
+        level: ProfileLevel,
+        sort_key: SortKey,
+        limit: Option<usize>,
+    ) -> Vec<(&str, &ProfileEntry)> {
+        let mut entries: Vec<_> = self
+            .entries(level)
+            .iter()
+            .map(|(name, entry)| (name.as_str(), entry))
+            .collect();
+        entries.sort_by(|(name_a, entry_a), (name_b, entry_b)| {
+            sort_key
+                .value(entry_b)
+                .cmp(&sort_key.value(entry_a))
+                .then_with(|| name_a.cmp(name_b))
+        });
+        entries.truncate(limit.unwrap_or(entries.len()));
+        entries
+    }
+}
+
+impl ProfileLevel {
+    /// Name of the entry the function is aggregated under.
+    /// Names that cannot be parsed are aggregated under themselves.
+    fn key(self, function_name: &FunctionName) -> String {
+        let Ok(parsed) = function_name.parse() else {
+            return function_name.0.clone();
+        };
+        match self {
+            ProfileLevel::Function => function_name.0.clone(),
+            ProfileLevel::Module => parsed.module(),
+            ProfileLevel::Crate => parsed
+                .crate_name
+                .unwrap_or(parsed.function.name)
+                .to_string(),
+        }
+    }
+}
+
+impl SortKey {
+    fn value(self, entry: &ProfileEntry) -> u64 {
+        match self {
+            SortKey::SelfSteps => entry.self_steps as u64,
+            SortKey::TotalSteps => entry.total_steps as u64,
+            SortKey::SelfGas => entry.self_gas,
+            SortKey::TotalGas => entry.total_gas,
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+
+    pub(crate) fn trace_entry(pc: usize, fp: usize) -> TraceEntry {
+        TraceEntry { pc, ap: fp, fp }
+    }
+
+    pub(crate) fn profiler_annotations(
+        statements: impl IntoIterator<Item = (usize, Vec<&'static str>)>,
+    ) -> ProfilerAnnotationsV1 {
+        ProfilerAnnotationsV1 {
+            statements_functions: statements
+                .into_iter()
+                .map(|(statement, names)| {
+                    (
+                        StatementIdx(statement),
+                        names
+                            .into_iter()
+                            .map(|name| FunctionName(name.to_string()))
+                            .collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// `main` (statements 0-2) calls `helper` (statements 3-4), which inlines `core::integer::u8_add`.
+    pub(crate) fn profile() -> Profile {
+        let annotations = profiler_annotations([
+            (0, vec!["pkg::main"]),
+            (1, vec!["pkg::main"]),
+            (2, vec!["pkg::main"]),
+            (3, vec!["core::integer::u8_add", "pkg::utils::helper"]),
+            (4, vec!["pkg::utils::helper"]),
+        ]);
+        let statement = |statement| MappingResult::SierraStatementIdx(StatementIdx(statement));
+        let vm_trace = [
+            trace_entry(0, 10),
+            trace_entry(1, 10),
+            trace_entry(3, 20),
+            trace_entry(3, 20),
+            trace_entry(4, 20),
+            trace_entry(2, 10),
+            trace_entry(9, 10),
+        ];
+        let mapping_results = [
+            statement(0),
+            statement(1),
+            statement(3),
+            statement(3),
+            statement(4),
+            statement(2),
+            MappingResult::PcOutOfFunctionArea,
+        ];
+        let statement_gas = HashMap::from([
+            (StatementIdx(0), 100),
+            (StatementIdx(1), 200),
+            (StatementIdx(2), 0),
+            (StatementIdx(3), 300),
+            (StatementIdx(4), 50),
+        ]);
+
+        let mut profile = Profile::default();
+        profile.record_trace(
+            &annotations,
+            &vm_trace,
+            &mapping_results,
+            Some(&statement_gas),
+        );
+        profile
+    }
+
+    #[test]
+    fn test_record_trace() {
+        let profile = profile();
+
+        assert_eq!(profile.total_steps, 6);
+        assert_eq!(profile.total_gas, 650);
+        assert_eq!(
+            profile.functions,
+            BTreeMap::from([
+                (
+                    "pkg::main".to_string(),
+                    ProfileEntry {
+                        self_steps: 3,
+                        total_steps: 6,
+                        self_gas: 300,
+                        total_gas: 650,
+                    }
+                ),
+                (
+                    "pkg::utils::helper".to_string(),
+                    ProfileEntry {
+                        self_steps: 1,
+                        total_steps: 3,
+                        self_gas: 50,
+                        total_gas: 350,
+                    }
+                ),
+                (
+                    "core::integer::u8_add".to_string(),
+                    ProfileEntry {
+                        self_steps: 2,
+                        total_steps: 2,
+                        self_gas: 300,
+                        total_gas: 300,
+                    }
+                ),
+            ])
+        );
+        assert_eq!(profile.modules["pkg::utils"].total_steps, 3);
+        assert_eq!(profile.modules["pkg"].self_steps, 3);
+        assert_eq!(
+            profile.crates["pkg"],
+            ProfileEntry {
+                self_steps: 4,
+                total_steps: 6,
+                self_gas: 350,
+                total_gas: 650,
+            }
+        );
+        assert_eq!(profile.crates["core"].total_steps, 2);
+    }
+
+    #[test]
+    fn test_unknown_statements_and_recursion() {
+        let annotations = profiler_annotations([(0, vec!["pkg::fib"])]);
+        let statement = |statement| MappingResult::SierraStatementIdx(StatementIdx(statement));
+        let vm_trace = [trace_entry(0, 10), trace_entry(0, 20), trace_entry(1, 20)];
+        let mapping_results = [statement(0), statement(0), statement(1)];
+
+        let mut profile = Profile::default();
+        profile.record_trace(&annotations, &vm_trace, &mapping_results, None);
+
+        assert!(!profile.gas_recorded);
+        assert_eq!(profile.functions["pkg::fib"].self_steps, 2);
+        // Recursive calls are counted once in the total.
+        assert_eq!(profile.functions["pkg::fib"].total_steps, 3);
+        assert_eq!(profile.functions[UNKNOWN_FUNCTION].self_steps, 1);
+        assert_eq!(profile.crates[UNKNOWN_FUNCTION].self_steps, 1);
+    }
+
+    #[test]
+    fn test_top() {
+        let profile = profile();
+
+        let names = |sort_key, limit| {
+            profile
+                .top(ProfileLevel::Function, sort_key, limit)
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(SortKey::SelfSteps, None),
+            vec!["pkg::main", "core::integer::u8_add", "pkg::utils::helper"]
+        );
+        assert_eq!(
+            names(SortKey::SelfGas, Some(2)),
+            vec!["core::integer::u8_add", "pkg::main"]
+        );
+        assert_eq!(
+            profile
+                .top(ProfileLevel::Crate, SortKey::TotalSteps, Some(1))
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>(),
+            vec!["pkg"]
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use crate::MappingResult;
+use crate::annotations::attribution::AttributionPolicy;
+use crate::annotations::coverage::{CodeLocation, CoverageAnnotationsV1};
+use crate::annotations::profiler::{FunctionName, ProfilerAnnotationsV1};
+use crate::profiling::UNKNOWN_FUNCTION;
+use crate::profiling::call_stacks::CallStacks;
+use crate::trace_data::{DeprecatedSyscallSelector, TraceEntry};
+use cairo_lang_sierra::ids::ConcreteLibfuncId;
+use cairo_lang_sierra::program::{Program, Statement, StatementIdx};
+use std::collections::{BTreeMap, HashMap};
+
+/// Sierra statements invoking syscall libfuncs.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SyscallStatements {
+    pub statements: BTreeMap<StatementIdx, DeprecatedSyscallSelector>,
+}
+
+/// A single syscall executed in a trace, with the Cairo code that triggered it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SyscallInvocation<'a> {
+    pub selector: DeprecatedSyscallSelector,
+    /// Statement invoking the syscall libfunc.
+    pub statement_idx: StatementIdx,
+    /// Cairo functions the syscall was executed in, from the outermost to the innermost,
+    /// including both non-inlined callers and inlined functions.
+    pub call_stack: Vec<FunctionName>,
+    /// Source code locations of the statement, selected by the attribution policy.
+    /// Empty if coverage annotations were not provided.
+    pub code_locations: Vec<&'a CodeLocation>,
+}
+
+impl SyscallStatements {
+    /// Finds statements of `program` invoking syscall libfuncs, e.g. `storage_read_syscall`.
+    #[must_use]
+    pub fn new(program: &Program) -> Self {
+        let syscall_libfuncs: HashMap<&ConcreteLibfuncId, DeprecatedSyscallSelector> = program
+            .libfunc_declarations
+            .iter()
+            .filter_map(|declaration| {
+                syscall_selector(declaration.long_id.generic_id.0.as_str())
+                    .map(|selector| (&declaration.id, selector))
+            })
+            .collect();
+
+        let statements = program
+            .statements
+            .iter()
+            .enumerate()
+            .filter_map(|(index, statement)| match statement {
+                Statement::Invocation(invocation) => syscall_libfuncs
+                    .get(&invocation.libfunc_id)
+                    .map(|&selector| (StatementIdx(index), selector)),
+                Statement::Return(_) => None,
+            })
+            .collect();
+
+        Self { statements }
+    }
+
+    /// Lists syscalls executed in a trace mapped with
+    /// [`map_pcs_to_sierra_statement_ids`](crate::map_pcs_to_sierra_statement_ids), in the order of execution.
+    ///
+    /// `vm_trace` must be the trace the `mapping_results` were created from. Call stacks are built
+    /// the same way as in [`Profile::record_trace`](crate::profiling::Profile::record_trace).
+    /// Code locations are taken from `coverage_annotations` and selected by `policy`.
+    #[must_use]
+    pub fn locate<'a>(
+        &self,
+        profiler_annotations: &ProfilerAnnotationsV1,
+        coverage_annotations: Option<&'a CoverageAnnotationsV1>,
+        policy: AttributionPolicy,
+        vm_trace: &[TraceEntry],
+        mapping_results: &[MappingResult],
+    ) -> Vec<SyscallInvocation<'a>> {
+        let unknown_function = FunctionName(UNKNOWN_FUNCTION.to_string());
+        let mut call_stacks = CallStacks::new(profiler_annotations, &unknown_function);
+        let mut invocations = Vec::new();
+
+        for (trace_entry, &mapping_result) in vm_trace.iter().zip(mapping_results) {
+            let statement_idx = Option::<StatementIdx>::from(mapping_result);
+            let (call_stack_id, new_execution) = call_stacks.step(trace_entry.fp, statement_idx);
+
+            let Some(statement_idx) = statement_idx.filter(|_| new_execution) else {
+                continue;
+            };
+            let Some(&selector) = self.statements.get(&statement_idx) else {
+                continue;
+            };
+            invocations.push(SyscallInvocation {
+                selector,
+                statement_idx,
+                call_stack: call_stacks
+                    .frames(call_stack_id, statement_idx)
+                    .into_iter()
+                    .cloned()
+                    .collect(),
+                code_locations: coverage_annotations
+                    .map(|annotations| annotations.resolve_locations(statement_idx, policy))
+                    .unwrap_or_default(),
+            });
+        }
+
+        invocations
+    }
+}
+
+/// Number of invocations of each syscall, comparable with the syscall counter of
+/// [`ExecutionResources`](crate::trace_data::ExecutionResources).
+#[must_use]
+pub fn count_syscalls(
+    invocations: &[SyscallInvocation<'_>],
+) -> HashMap<DeprecatedSyscallSelector, usize> {
+    let mut counts = HashMap::new();
+    for invocation in invocations {
+        *counts.entry(invocation.selector).or_default() += 1;
+    }
+    counts
+}
+
+/// Finds the syscall invoked by a libfunc by the name of its generic libfunc, e.g. `storage_read_syscall`.
+#[must_use]
+pub fn syscall_selector(libfunc_name: &str) -> Option<DeprecatedSyscallSelector> {
+    let selector = match libfunc_name {
+        "call_contract_syscall" => DeprecatedSyscallSelector::CallContract,
+        "deploy_syscall" => DeprecatedSyscallSelector::Deploy,
+        "emit_event_syscall" => DeprecatedSyscallSelector::EmitEvent,
+        "get_block_hash_syscall" => DeprecatedSyscallSelector::GetBlockHash,
+        "get_class_hash_at_syscall" => DeprecatedSyscallSelector::GetClassHashAt,
+        "get_execution_info_syscall"
+        | "get_execution_info_v2_syscall"
+        | "get_execution_info_v3_syscall" => DeprecatedSyscallSelector::GetExecutionInfo,
+        "keccak_syscall" => DeprecatedSyscallSelector::Keccak,
+        "library_call_syscall" => DeprecatedSyscallSelector::LibraryCall,
+        "meta_tx_v0_syscall" => DeprecatedSyscallSelector::MetaTxV0,
+        "replace_class_syscall" => DeprecatedSyscallSelector::ReplaceClass,
+        "secp256k1_add_syscall" => DeprecatedSyscallSelector::Secp256k1Add,
+        "secp256k1_get_point_from_x_syscall" => DeprecatedSyscallSelector::Secp256k1GetPointFromX,
+        "secp256k1_get_xy_syscall" => DeprecatedSyscallSelector::Secp256k1GetXy,
+        "secp256k1_mul_syscall" => DeprecatedSyscallSelector::Secp256k1Mul,
+        "secp256k1_new_syscall" => DeprecatedSyscallSelector::Secp256k1New,
+        "secp256r1_add_syscall" => DeprecatedSyscallSelector::Secp256r1Add,
+        "secp256r1_get_point_from_x_syscall" => DeprecatedSyscallSelector::Secp256r1GetPointFromX,
+        "secp256r1_get_xy_syscall" => DeprecatedSyscallSelector::Secp256r1GetXy,
+        "secp256r1_mul_syscall" => DeprecatedSyscallSelector::Secp256r1Mul,
+        "secp256r1_new_syscall" => DeprecatedSyscallSelector::Secp256r1New,
+        "send_message_to_l1_syscall" => DeprecatedSyscallSelector::SendMessageToL1,
+        "sha256_process_block_syscall" => DeprecatedSyscallSelector::Sha256ProcessBlock,
+        "storage_read_syscall" => DeprecatedSyscallSelector::StorageRead,
+        "storage_write_syscall" => DeprecatedSyscallSelector::StorageWrite,
+        _ => return None,
+    };
+    Some(selector)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::coverage_report::test::{annotations, location};
+    use crate::profiling::test::{profiler_annotations, trace_entry};
+    use cairo_lang_sierra::ProgramParser;
+
+    /// 0: `storage_read_syscall` - to 1 or 2
+    /// 3: `emit_event_syscall` - to 4 or 5
+    const PROGRAM: &str = "
+        type GasBuiltin = GasBuiltin;
+        type System = System;
+        type felt252 = felt252;
+        type StorageAddress = StorageAddress;
+
+        libfunc storage_read_syscall = storage_read_syscall;
+        libfunc emit_event_syscall = emit_event_syscall;
+
+        storage_read_syscall(gas, system, domain, address) { fallthrough(gas, system, value) 2(gas, system, error) };
+        return(gas, system, value);
+        return(gas, system, error);
+        emit_event_syscall(gas, system, keys, data) { fallthrough(gas, system) 5(gas, system, error) };
+        return(gas, system);
+        return(gas, system, error);
+
+        test_program@0(gas: GasBuiltin, system: System, domain: felt252, address: StorageAddress) -> (GasBuiltin, System, felt252);
+    ";
+
+    fn syscall_statements() -> SyscallStatements {
+        SyscallStatements::new(&ProgramParser::new().parse(PROGRAM).unwrap())
+    }
+
+    #[test]
+    fn test_syscall_statements() {
+        assert_eq!(
+            syscall_statements().statements,
+            BTreeMap::from([
+                (StatementIdx(0), DeprecatedSyscallSelector::StorageRead),
+                (StatementIdx(3), DeprecatedSyscallSelector::EmitEvent),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_locate() {
+        // `pkg::main` (statements 6-7) calls `pkg::read_balance` twice, which inlines `storage_read`.
+        let profiler_annotations = profiler_annotations([
+            (0, vec!["core::starknet::storage_read", "pkg::read_balance"]),
+            (1, vec!["pkg::read_balance"]),
+            (6, vec!["pkg::main"]),
+            (7, vec!["pkg::main"]),
+        ]);
+        let coverage_annotations = annotations([(0, vec![location("src/lib.cairo", 11, None)])]);
+        let statement = |statement| MappingResult::SierraStatementIdx(StatementIdx(statement));
+        let vm_trace = [
+            trace_entry(0, 10),
+            trace_entry(1, 20),
+            trace_entry(1, 20),
+            trace_entry(2, 20),
+            trace_entry(3, 10),
+            trace_entry(1, 20),
+            trace_entry(2, 20),
+            trace_entry(3, 10),
+        ];
+        let mapping_results = [
+            statement(6),
+            statement(0),
+            statement(0),
+            statement(1),
+            statement(7),
+            statement(0),
+            statement(1),
+            statement(7),
+        ];
+
+        let invocations = syscall_statements().locate(
+            &profiler_annotations,
+            Some(&coverage_annotations),
+            AttributionPolicy::AllFrames,
+            &vm_trace,
+            &mapping_results,
+        );
+
+        let expected = SyscallInvocation {
+            selector: DeprecatedSyscallSelector::StorageRead,
+            statement_idx: StatementIdx(0),
+            call_stack: [
+                "pkg::main",
+                "pkg::read_balance",
+                "core::starknet::storage_read",
+            ]
+            .map(|name| FunctionName(name.to_string()))
+            .to_vec(),
+            code_locations: vec![
+                &coverage_annotations.statements_code_locations[&StatementIdx(0)][0],
+            ],
+        };
+        assert_eq!(invocations, vec![expected.clone(), expected]);
+        assert_eq!(
+            count_syscalls(&invocations),
+            HashMap::from([(DeprecatedSyscallSelector::StorageRead, 2)])
+        );
+    }
+
+    #[test]
+    fn test_syscall_selector() {
+        assert_eq!(
+            syscall_selector("get_execution_info_v2_syscall"),
+            Some(DeprecatedSyscallSelector::GetExecutionInfo)
+        );
+        assert_eq!(syscall_selector("storage_read"), None);
+    }
+}
@@ -0,0 +1,270 @@
+use crate::profiling::libfuncs::{HistogramWeight, LibfuncHistogram, LibfuncLevel};
+use crate::profiling::{Profile, ProfileLevel, SortKey};
+use std::fmt::Write;
+
+/// Format of a table rendered with [`render_table`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum TableFormat {
+    /// Columns aligned with spaces, suitable for terminals and CI logs.
+    #[default]
+    PlainText,
+    /// GitHub flavored Markdown table, suitable for PR comments and job summaries.
+    Markdown,
+}
+
+/// Renders the entries of the profile aggregated at `level` as a table, sorted by `sort_key`.
+/// At most `limit` entries are rendered, if provided.
+///
+/// Percentages are relative to the total cost of the profile.
+/// Gas columns are only rendered if gas was recorded.
+#[must_use]
+pub fn render_table(
+    profile: &Profile,
+    level: ProfileLevel,
+    sort_key: SortKey,
+    limit: Option<usize>,
+    format: TableFormat,
+) -> String {
+    let name_header = match level {
+        ProfileLevel::Function => "Function",
+        ProfileLevel::Module => "Module",
+        ProfileLevel::Crate => "Crate",
+    };
+    let mut header = vec![
+        name_header,
+        "Self steps",
+        "Self %",
+        "Total steps",
+        "Total %",
+    ];
+    if profile.gas_recorded {
+        header.extend(["Self gas", "Total gas"]);
+    }
+
+    let rows: Vec<Vec<String>> = profile
+        .top(level, sort_key, limit)
+        .into_iter()
+        .map(|(name, entry)| {
+            let mut row = vec![
+                name.to_string(),
+                entry.self_steps.to_string(),
+                percentage(entry.self_steps, profile.total_steps),
+                entry.total_steps.to_string(),
+                percentage(entry.total_steps, profile.total_steps),
+            ];
+            if profile.gas_recorded {
+                row.extend([entry.self_gas.to_string(), entry.total_gas.to_string()]);
+            }
+            row
+        })
+        .collect();
+
+    let header: Vec<String> = header.into_iter().map(ToString::to_string).collect();
+    match format {
+        TableFormat::PlainText => render_plain_text(&header, &rows),
+        TableFormat::Markdown => render_markdown(&header, &rows),
+    }
+}
+
+/// Renders the entries of the libfunc histogram aggregated at `level` as a table, sorted by `weight`.
+/// At most `limit` entries are rendered, if provided.
+///
+/// Percentages are relative to the total cost of the histogram.
+/// Memory hole columns are only rendered if memory holes were recorded.
+#[must_use]
+pub fn render_libfunc_table(
+    histogram: &LibfuncHistogram,
+    level: LibfuncLevel,
+    weight: HistogramWeight,
+    limit: Option<usize>,
+    format: TableFormat,
+) -> String {
+    let name_header = match level {
+        LibfuncLevel::Generic => "Libfunc",
+        LibfuncLevel::Concrete => "Concrete libfunc",
+    };
+    let mut header = vec![name_header, "Steps", "Steps %"];
+    if histogram.memory_holes_recorded {
+        header.extend(["Memory holes", "Steps + holes %"]);
+    }
+
+    let rows: Vec<Vec<String>> = histogram
+        .top(level, weight, limit)
+        .into_iter()
+        .map(|(name, cost)| {
+            let mut row = vec![
+                name.to_string(),
+                cost.steps.to_string(),
+                percentage(cost.steps, histogram.total.steps),
+            ];
+            if histogram.memory_holes_recorded {
+                let weighted = HistogramWeight::StepsAndMemoryHoles;
+                row.extend([
+                    cost.memory_holes.to_string(),
+                    percentage(cost.weight(weighted), histogram.total.weight(weighted)),
+                ]);
+            }
+            row
+        })
+        .collect();
+
+    let header: Vec<String> = header.into_iter().map(ToString::to_string).collect();
+    match format {
+        TableFormat::PlainText => render_plain_text(&header, &rows),
+        TableFormat::Markdown => render_markdown(&header, &rows),
+    }
+}
+
+#[expect(clippy::cast_precision_loss)]
+fn percentage(value: usize, total: usize) -> String {
+    if total == 0 {
+        return "0.00%".to_string();
+    }
+    format!("{:.2}%", value as f64 / total as f64 * 100.0)
+}
+
+/// Names are aligned to the left and numbers to the right.
+fn render_plain_text(header: &[String], rows: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| {
+            std::iter::once(header)
+                .chain(rows.iter().map(Vec::as_slice))
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let render_row = |row: &[String]| {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(column, (cell, &width))| {
+                if column == 0 {
+                    format!("{cell:<width$}")
+                } else {
+                    format!("{cell:>width$}")
+                }
+            })
+            .collect();
+        cells.join("  ").trim_end().to_string()
+    };
+
+    let mut table = String::new();
+    let _ = writeln!(table, "{}", render_row(header));
+    let separator: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+    let _ = writeln!(table, "{}", separator.join("  "));
+    for row in rows {
+        let _ = writeln!(table, "{}", render_row(row));
+    }
+    table
+}
+
+fn render_markdown(header: &[String], rows: &[Vec<String>]) -> String {
+    let mut table = String::new();
+    let _ = writeln!(table, "| {} |", header.join(" | "));
+    let alignments: Vec<&str> = (0..header.len())
+        .map(|column| if column == 0 { ":---" } else { "---:" })
+        .collect();
+    let _ = writeln!(table, "| {} |", alignments.join(" | "));
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|cell| cell.replace('|', "\\|")).collect();
+        let _ = writeln!(table, "| {} |", cells.join(" | "));
+    }
+    table
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::profiling::libfuncs::LibfuncCost;
+    use crate::profiling::test::profile;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_render_plain_text() {
+        let table = render_table(
+            &profile(),
+            ProfileLevel::Function,
+            SortKey::TotalSteps,
+            Some(2),
+            TableFormat::PlainText,
+        );
+
+        assert_eq!(
+            table,
+            [
+                "Function            Self steps  Self %  Total steps  Total %  Self gas  Total gas",
+                "------------------  ----------  ------  -----------  -------  --------  ---------",
+                "pkg::main                    3  50.00%            6  100.00%       300        650",
+                "pkg::utils::helper           1  16.67%            3   50.00%        50        350",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let mut profile = profile();
+        profile.gas_recorded = false;
+
+        let table = render_table(
+            &profile,
+            ProfileLevel::Crate,
+            SortKey::SelfSteps,
+            None,
+            TableFormat::Markdown,
+        );
+
+        assert_eq!(
+            table,
+            [
+                "| Crate | Self steps | Self % | Total steps | Total % |",
+                "| :--- | ---: | ---: | ---: | ---: |",
+                "| pkg | 4 | 66.67% | 6 | 100.00% |",
+                "| core | 2 | 33.33% | 2 | 33.33% |",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_render_libfunc_table() {
+        let cost = |steps, memory_holes| LibfuncCost {
+            steps,
+            memory_holes,
+        };
+        let histogram = LibfuncHistogram {
+            generic: BTreeMap::from([
+                ("array_append".to_string(), cost(6, 0)),
+                ("branch_align".to_string(), cost(2, 2)),
+            ]),
+            total: cost(8, 2),
+            memory_holes_recorded: true,
+            ..LibfuncHistogram::default()
+        };
+
+        let table = render_libfunc_table(
+            &histogram,
+            LibfuncLevel::Generic,
+            HistogramWeight::Steps,
+            None,
+            TableFormat::Markdown,
+        );
+
+        assert_eq!(
+            table,
+            [
+                "| Libfunc | Steps | Steps % | Memory holes | Steps + holes % |",
+                "| :--- | ---: | ---: | ---: | ---: |",
+                "| array_append | 6 | 75.00% | 0 | 60.00% |",
+                "| branch_align | 2 | 25.00% | 2 | 40.00% |",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use cairo_lang_sierra_to_casm::compiler::CairoProgram;
+use starknet_types_core::felt::Felt;
+use std::fmt;
+use thiserror::Error;
+
+/// Location of a compiled program in the bytecode that was actually run.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DetectedProgramOffset {
+    /// Number of bytecode felts preceding the program, e.g. of headers added by the runner.
+    /// To be used as [`CasmLevelInfo::program_offset`](crate::trace_data::CasmLevelInfo::program_offset).
+    pub program_offset: usize,
+    pub confidence: OffsetConfidence,
+}
+
+/// How certain the detected program offset is.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OffsetConfidence {
+    /// The program occurs in the bytecode exactly once.
+    Unique,
+    /// The program occurs in the bytecode multiple times, at the given offsets. The first one is used.
+    Ambiguous(Vec<usize>),
+}
+
+/// Enum representing the possible errors that can occur when detecting the program offset.
+#[derive(Clone, Debug, Eq, PartialEq, Error)]
+pub enum ProgramOffsetError {
+    /// Error indicating that the compiled program has no instructions.
+    #[error("Program has no instructions")]
+    EmptyProgram,
+
+    /// Error indicating that the program does not occur in the bytecode, most likely because it was compiled
+    /// differently than the program that was run.
+    #[error(
+        "Program not found in bytecode, the longest match is {matched_len} out of {program_len} felts at offset {offset}"
+    )]
+    NotFound {
+        /// Offset at which the longest prefix of the program occurs.
+        offset: usize,
+        /// Length of the longest prefix of the program that occurs in the bytecode.
+        matched_len: usize,
+        program_len: usize,
+    },
+}
+
+impl fmt::Display for DetectedProgramOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "program found at offset {}", self.program_offset)?;
+        match &self.confidence {
+            OffsetConfidence::Unique => Ok(()),
+            OffsetConfidence::Ambiguous(offsets) => {
+                let offsets: Vec<String> = offsets.iter().map(ToString::to_string).collect();
+                write!(f, ", ambiguous between offsets {}", offsets.join(", "))
+            }
+        }
+    }
+}
+
+/// Locates the compiled `program` in the full `bytecode` that was run by matching their contents.
+///
+/// Runners may wrap the program in headers of various sizes, e.g. `scarb execute` standalone targets.
+/// The detected offset can be passed as [`CasmLevelInfo::program_offset`](crate::trace_data::CasmLevelInfo::program_offset)
+/// to [`map_pcs_to_sierra_statement_ids`](crate::map_pcs_to_sierra_statement_ids), without knowing which runner produced
+/// the trace.
+///
+/// # Errors
+///
+/// Returns an error if the program is empty or does not occur in the bytecode.
+pub fn detect_program_offset(
+    bytecode: &[Felt],
+    program: &CairoProgram,
+) -> Result<DetectedProgramOffset, ProgramOffsetError> {
+    let program_bytecode: Vec<Felt> = program.assemble().bytecode.iter().map(Felt::from).collect();
+    if program_bytecode.is_empty() {
+        return Err(ProgramOffsetError::EmptyProgram);
+    }
+
+    let offsets: Vec<usize> = bytecode
+        .windows(program_bytecode.len())
+        .enumerate()
+        .filter(|(_, window)| *window == program_bytecode.as_slice())
+        .map(|(offset, _)| offset)
+        .collect();
+
+    match offsets.as_slice() {
+        [] => {
+            let (offset, matched_len) = longest_prefix_match(bytecode, &program_bytecode);
+            Err(ProgramOffsetError::NotFound {
+                offset,
+                matched_len,
+                program_len: program_bytecode.len(),
+            })
+        }
+        [offset] => Ok(DetectedProgramOffset {
+            program_offset: *offset,
+            confidence: OffsetConfidence::Unique,
+        }),
+        [offset, ..] => Ok(DetectedProgramOffset {
+            program_offset: *offset,
+            confidence: OffsetConfidence::Ambiguous(offsets.clone()),
+        }),
+    }
+}
+
+/// Finds the offset in `bytecode` at which the longest prefix of `program_bytecode` occurs.
+fn longest_prefix_match(bytecode: &[Felt], program_bytecode: &[Felt]) -> (usize, usize) {
+    (0..bytecode.len())
+        .map(|offset| {
+            let matched_len = bytecode[offset..]
+                .iter()
+                .zip(program_bytecode)
+                .take_while(|(left, right)| left == right)
+                .count();
+            (offset, matched_len)
+        })
+        // Prefer the first offset on ties.
+        .max_by_key(|&(offset, matched_len)| (matched_len, std::cmp::Reverse(offset)))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::map_pcs_to_casm_instructions::test::compile_program;
+
+    const PROGRAM: &str = "
+        type felt252 = felt252;
+        type NonZeroFelt252 = NonZero<felt252>;
+
+        libfunc felt252_is_zero = felt252_is_zero;
+        libfunc branch_align = branch_align;
+        libfunc drop<NonZeroFelt252> = drop<NonZeroFelt252>;
+
+        felt252_is_zero(a) { fallthrough() 3(a) };
+        branch_align() -> ();
+        return();
+        branch_align() -> ();
+        drop<NonZeroFelt252>(a) -> ();
+        return();
+
+        pkg::main@0(a: felt252) -> ();
+    ";
+
+    fn cairo_program() -> CairoProgram {
+        compile_program(PROGRAM).1
+    }
+
+    fn program_bytecode(program: &CairoProgram) -> Vec<Felt> {
+        program.assemble().bytecode.iter().map(Felt::from).collect()
+    }
+
+    #[test]
+    fn test_detect_program_offset() {
+        let program = cairo_program();
+        let header = [Felt::from(0x1234), Felt::from(0x5678), Felt::from(1)];
+        let bytecode = [
+            header.as_slice(),
+            &program_bytecode(&program),
+            &[Felt::ZERO],
+        ]
+        .concat();
+
+        let detected = detect_program_offset(&bytecode, &program).unwrap();
+
+        assert_eq!(
+            detected,
+            DetectedProgramOffset {
+                program_offset: 3,
+                confidence: OffsetConfidence::Unique,
+            }
+        );
+        assert_eq!(detected.to_string(), "program found at offset 3");
+    }
+
+    #[test]
+    fn test_detect_program_offset_ambiguous() {
+        let program = cairo_program();
+        let program_bytecode = program_bytecode(&program);
+        let bytecode = [
+            program_bytecode.as_slice(),
+            &[Felt::ZERO],
+            &program_bytecode,
+        ]
+        .concat();
+
+        let detected = detect_program_offset(&bytecode, &program).unwrap();
+
+        let second = program_bytecode.len() + 1;
+        assert_eq!(detected.program_offset, 0);
+        assert_eq!(
+            detected.confidence,
+            OffsetConfidence::Ambiguous(vec![0, second])
+        );
+        assert_eq!(
+            detected.to_string(),
+            format!("program found at offset 0, ambiguous between offsets 0, {second}")
+        );
+    }
+
+    #[test]
+    fn test_detect_program_offset_not_found() {
+        let program = cairo_program();
+        let mut program_bytecode = program_bytecode(&program);
+        let program_len = program_bytecode.len();
+        *program_bytecode.last_mut().unwrap() += Felt::ONE;
+        let bytecode = [&[Felt::ZERO], program_bytecode.as_slice()].concat();
+
+        assert_eq!(
+            detect_program_offset(&bytecode, &program),
+            Err(ProgramOffsetError::NotFound {
+                offset: 1,
+                matched_len: program_len - 1,
+                program_len,
+            })
+        );
+    }
+}
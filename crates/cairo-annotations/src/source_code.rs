@@ -0,0 +1,574 @@
+use crate::annotations::coverage::{
+    ColumnNumber, LineNumber, SourceCodeLocation, SourceCodeSpan, SourceFileFullPath,
+    VirtualFilePathError,
+};
+use camino::{Utf8Path, Utf8PathBuf};
+use std::collections::HashMap;
+use std::{env, fs, io};
+use thiserror::Error;
+
+/// Source of Cairo files contents used to resolve [`SourceCodeSpan`]s.
+pub trait SourceProvider {
+    /// Returns the contents of the file at the given path.
+    ///
+    /// The path never contains virtual file markings.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be found or read.
+    fn read_source(&self, path: &str) -> Result<String, SourceError>;
+}
+
+/// Reads Cairo files directly from the filesystem.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FileSystemSourceProvider;
+
+impl SourceProvider for FileSystemSourceProvider {
+    fn read_source(&self, path: &str) -> Result<String, SourceError> {
+        read_file(Utf8Path::new(path), path)
+    }
+}
+
+/// Serves Cairo files from memory, e.g. when the sources were shipped alongside the trace.
+#[derive(Clone, Debug, Default)]
+pub struct InMemorySourceProvider {
+    sources: HashMap<String, String>,
+}
+
+impl InMemorySourceProvider {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the contents of a file, replacing the previous one under the same path.
+    pub fn insert(&mut self, path: impl Into<String>, contents: impl Into<String>) {
+        self.sources.insert(path.into(), contents.into());
+    }
+}
+
+impl From<HashMap<String, String>> for InMemorySourceProvider {
+    fn from(sources: HashMap<String, String>) -> Self {
+        Self { sources }
+    }
+}
+
+impl SourceProvider for InMemorySourceProvider {
+    fn read_source(&self, path: &str) -> Result<String, SourceError> {
+        self.sources
+            .get(path)
+            .cloned()
+            .ok_or_else(|| SourceError::NotFound(path.to_string()))
+    }
+}
+
+/// Reads Cairo files of dependencies from the local Scarb package cache.
+///
+/// Paths of dependencies point to the Scarb cache of the machine that compiled the program,
+/// e.g. `/home/ci/.cache/scarb/registry/std/v2.11.0/core/src/lib.cairo`.
+/// The part of such path starting at the `registry` or `git` directory is looked up in the local cache first,
+/// and the path is read as is if it is not there.
+#[derive(Clone, Debug)]
+pub struct ScarbCacheSourceProvider {
+    cache_dir: Utf8PathBuf,
+}
+
+impl ScarbCacheSourceProvider {
+    #[must_use]
+    pub fn new(cache_dir: Utf8PathBuf) -> Self {
+        Self { cache_dir }
+    }
+
+    /// Creates the provider for the Scarb cache directory of the current user.
+    ///
+    /// Respects the `SCARB_CACHE` environment variable and falls back to the platform default location.
+    /// Returns `None` if the location cannot be determined.
+    #[must_use]
+    pub fn from_env() -> Option<Self> {
+        if let Ok(cache_dir) = env::var("SCARB_CACHE") {
+            return Some(Self::new(cache_dir.into()));
+        }
+
+        let cache_dir = if cfg!(target_os = "macos") {
+            Utf8PathBuf::from(env::var("HOME").ok()?).join("Library/Caches/com.swmansion.scarb")
+        } else if cfg!(target_os = "windows") {
+            Utf8PathBuf::from(env::var("LOCALAPPDATA").ok()?).join("swmansion/scarb/cache")
+        } else if let Ok(xdg_cache_home) = env::var("XDG_CACHE_HOME") {
+            Utf8PathBuf::from(xdg_cache_home).join("scarb")
+        } else {
+            Utf8PathBuf::from(env::var("HOME").ok()?).join(".cache/scarb")
+        };
+
+        Some(Self::new(cache_dir))
+    }
+
+    #[must_use]
+    pub fn cache_dir(&self) -> &Utf8Path {
+        &self.cache_dir
+    }
+
+    fn relocate(&self, path: &str) -> Option<Utf8PathBuf> {
+        scarb_cache_relative_path(path).map(|relative_path| self.cache_dir.join(relative_path))
+    }
+}
+
+/// Directories of the Scarb cache holding sources of dependencies, fetched from registries and git repositories.
+const CACHE_SOURCE_DIRECTORIES: [&str; 2] = ["registry/", "git/checkouts/"];
+
+/// Default Scarb cache directories on Linux, macOS and Windows respectively, relative to the cache directory of the user.
+const DEFAULT_CACHE_DIR_SUFFIXES: [&str; 3] =
+    ["scarb", "com.swmansion.scarb", "swmansion/scarb/cache"];
+
+/// Returns the part of the path inside the Scarb cache directory, starting at the `registry` or `git` directory.
+///
+/// Returns `None` if the path does not point into a Scarb cache, i.e. it is not a dependency
+/// fetched by Scarb, like the core library or a package from a registry or git.
+///
+/// The cache is recognized by the default cache directory paths, e.g. `.../swmansion/scarb/cache` on Windows. Use [`scarb_cache_relative_path_in`]
+/// if the cache was in a custom location, e.g. set with `SCARB_CACHE`.
+#[must_use]
+pub fn scarb_cache_relative_path(path: &str) -> Option<String> {
+    let normalized = path.replace('\\', "/");
+    CACHE_SOURCE_DIRECTORIES.iter().find_map(|directory| {
+        normalized
+            .match_indices(&format!("/{directory}"))
+            .find(|(directory_start, _)| is_default_cache_dir(&normalized[..*directory_start]))
+            .map(|(directory_start, _)| normalized[directory_start + 1..].to_string())
+    })
+}
+
+fn is_default_cache_dir(path: &str) -> bool {
+    DEFAULT_CACHE_DIR_SUFFIXES.iter().any(|suffix| {
+        path.strip_suffix(suffix)
+            .is_some_and(|parent| parent.is_empty() || parent.ends_with('/'))
+    })
+}
+
+/// Returns the part of the path inside the Scarb cache directory `cache_dir`, starting at the `registry` or
+/// `git` directory.
+///
+/// Returns `None` if the path does not point into dependencies stored in `cache_dir`.
+#[must_use]
+pub fn scarb_cache_relative_path_in(path: &str, cache_dir: &Utf8Path) -> Option<String> {
+    let normalized = path.replace('\\', "/");
+    let cache_dir = cache_dir.as_str().replace('\\', "/");
+    let relative_path = normalized
+        .strip_prefix(cache_dir.trim_end_matches('/'))?
+        .strip_prefix('/')?;
+    CACHE_SOURCE_DIRECTORIES
+        .iter()
+        .any(|directory| relative_path.starts_with(directory))
+        .then(|| relative_path.to_string())
+}
+
+impl SourceProvider for ScarbCacheSourceProvider {
+    fn read_source(&self, path: &str) -> Result<String, SourceError> {
+        match self.relocate(path) {
+            Some(relocated) if relocated.is_file() => read_file(&relocated, path),
+            _ => read_file(Utf8Path::new(path), path),
+        }
+    }
+}
+
+fn read_file(file_path: &Utf8Path, requested_path: &str) -> Result<String, SourceError> {
+    fs::read_to_string(file_path).map_err(|source| match source.kind() {
+        io::ErrorKind::NotFound => SourceError::NotFound(requested_path.to_string()),
+        _ => SourceError::Io {
+            path: requested_path.to_string(),
+            source,
+        },
+    })
+}
+
+/// Unit in which [`ColumnNumber`]s are expressed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum ColumnEncoding {
+    /// Columns count UTF-8 bytes. This is what the Cairo compiler emits in annotations.
+    #[default]
+    Utf8,
+    /// Columns count UTF-16 code units, as used by e.g. the Language Server Protocol.
+    Utf16,
+}
+
+/// Contents of a Cairo source file with its lines indexed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SourceFile {
+    contents: String,
+    /// Byte offsets at which each line starts.
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    #[must_use]
+    pub fn new(contents: String) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(contents.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Self {
+            contents,
+            line_starts,
+        }
+    }
+
+    /// Reads the file pointed to by `path` using `provider`, ignoring virtual file markings.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the path is malformed or the file cannot be read.
+    pub fn read(
+        provider: &impl SourceProvider,
+        path: &SourceFileFullPath,
+    ) -> Result<Self, SourceError> {
+        let virtual_file_path = path.parse_virtual_file_path()?;
+        provider
+            .read_source(virtual_file_path.real_path)
+            .map(Self::new)
+    }
+
+    #[must_use]
+    pub fn contents(&self) -> &str {
+        &self.contents
+    }
+
+    /// Number of lines in the file.
+    #[must_use]
+    pub fn lines_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Returns the line with the given 0-based number, without the line terminator.
+    #[must_use]
+    pub fn line(&self, line: LineNumber) -> Option<&str> {
+        let start = *self.line_starts.get(line.0)?;
+        let end = self
+            .line_starts
+            .get(line.0 + 1)
+            .map_or(self.contents.len(), |next_start| next_start - 1);
+        let text = &self.contents[start..end];
+        Some(text.strip_suffix('\r').unwrap_or(text))
+    }
+
+    /// Returns all lines of the file, without line terminators.
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        (0..self.lines_count()).filter_map(|line| self.line(LineNumber(line)))
+    }
+
+    /// Resolves `span` against this file and returns the text it covers together with the full lines it touches.
+    ///
+    /// The end of the span is exclusive, so a span with the same start and end covers no text,
+    /// but still returns the line it points to.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the span points outside the file,
+    /// into the middle of a character, or its end is before its start.
+    pub fn snippet(
+        &self,
+        span: &SourceCodeSpan,
+        encoding: ColumnEncoding,
+    ) -> Result<SourceSnippet, SourceError> {
+        let start = self.offset(&span.start, encoding)?;
+        let end = self.offset(&span.end, encoding)?;
+        if end < start {
+            return Err(SourceError::InvalidSpan(span.clone()));
+        }
+
+        let lines = (span.start.line.0..=span.end.line.0)
+            .filter_map(|line| self.line(LineNumber(line)))
+            .map(ToString::to_string)
+            .collect();
+
+        Ok(SourceSnippet {
+            text: self.contents[start..end].to_string(),
+            first_line: span.start.line,
+            lines,
+        })
+    }
+
+    /// Converts a location into a byte offset in the file contents.
+    fn offset(
+        &self,
+        location: &SourceCodeLocation,
+        encoding: ColumnEncoding,
+    ) -> Result<usize, SourceError> {
+        let line_text = self
+            .line(location.line)
+            .ok_or(SourceError::LineOutOfRange(location.line))?;
+        let column_out_of_range = || SourceError::ColumnOutOfRange {
+            line: location.line,
+            col: location.col,
+        };
+
+        let ColumnNumber(col) = location.col;
+        let byte_col = match encoding {
+            ColumnEncoding::Utf8 => Some(col).filter(|&col| line_text.is_char_boundary(col)),
+            ColumnEncoding::Utf16 => std::iter::once((0, 0))
+                .chain(
+                    line_text
+                        .char_indices()
+                        .scan(0, |utf16_col, (byte_col, char)| {
+                            *utf16_col += char.len_utf16();
+                            Some((byte_col + char.len_utf8(), *utf16_col))
+                        }),
+                )
+                .find(|&(_, utf16_col)| utf16_col >= col)
+                .filter(|&(_, utf16_col)| utf16_col == col)
+                .map(|(byte_col, _)| byte_col),
+        }
+        .ok_or_else(column_out_of_range)?;
+
+        Ok(self.line_starts[location.line.0] + byte_col)
+    }
+}
+
+/// Fragment of a Cairo source file covered by a [`SourceCodeSpan`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SourceSnippet {
+    /// The exact text covered by the span.
+    pub text: String,
+    /// Number of the first line in `lines`, 0-based.
+    pub first_line: LineNumber,
+    /// Full lines touched by the span, without line terminators.
+    pub lines: Vec<String>,
+}
+
+/// Resolves `span` in the file at `path` and returns the source code it covers.
+///
+/// # Errors
+///
+/// This function will return an error if the file cannot be read or the span does not fit in it.
+///
+/// # Example
+/// ```
+/// use cairo_annotations::annotations::coverage::{
+///     ColumnNumber, LineNumber, SourceCodeLocation, SourceCodeSpan, SourceFileFullPath,
+/// };
+/// use cairo_annotations::source_code::{ColumnEncoding, InMemorySourceProvider, extract_snippet};
+///
+/// let mut provider = InMemorySourceProvider::new();
+/// provider.insert("/project/src/lib.cairo", "fn main() {\n    let x = 5;\n}\n");
+///
+/// let span = SourceCodeSpan {
+///     start: SourceCodeLocation { line: LineNumber(1), col: ColumnNumber(8) },
+///     end: SourceCodeLocation { line: LineNumber(1), col: ColumnNumber(9) },
+/// };
+/// let path = SourceFileFullPath("/project/src/lib.cairo[assert_macro]".to_string());
+///
+/// let snippet = extract_snippet(&provider, &path, &span, ColumnEncoding::Utf8).unwrap();
+/// assert_eq!(snippet.text, "x");
+/// assert_eq!(snippet.lines, vec!["    let x = 5;"]);
+/// ```
+pub fn extract_snippet(
+    provider: &impl SourceProvider,
+    path: &SourceFileFullPath,
+    span: &SourceCodeSpan,
+    encoding: ColumnEncoding,
+) -> Result<SourceSnippet, SourceError> {
+    SourceFile::read(provider, path)?.snippet(span, encoding)
+}
+
+/// Enum representing the possible errors that can occur when resolving source code.
+#[derive(Debug, Error)]
+pub enum SourceError {
+    /// Error indicating that the source file was not found.
+    #[error("Source file not found: {0}")]
+    NotFound(String),
+
+    /// Error indicating that the source file could not be read.
+    #[error("Failed to read source file {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+
+    /// Error indicating that the path of the source file is malformed.
+    #[error(transparent)]
+    InvalidPath(#[from] VirtualFilePathError),
+
+    /// Error indicating that the line is beyond the end of the file.
+    #[error("Line {0} is out of range")]
+    LineOutOfRange(LineNumber),
+
+    /// Error indicating that the column is beyond the end of the line or inside a character.
+    #[error("Column {col} is out of range in line {line}")]
+    ColumnOutOfRange { line: LineNumber, col: ColumnNumber },
+
+    /// Error indicating that the span ends before it starts.
+    #[error("Span ends before it starts: {0:?}")]
+    InvalidSpan(SourceCodeSpan),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn span(start: (usize, usize), end: (usize, usize)) -> SourceCodeSpan {
+        SourceCodeSpan {
+            start: SourceCodeLocation {
+                line: LineNumber(start.0),
+                col: ColumnNumber(start.1),
+            },
+            end: SourceCodeLocation {
+                line: LineNumber(end.0),
+                col: ColumnNumber(end.1),
+            },
+        }
+    }
+
+    #[test]
+    fn test_lines() {
+        let file = SourceFile::new("fn a() {}\r\nfn b() {}\n".to_string());
+        assert_eq!(
+            file.lines().collect::<Vec<_>>(),
+            vec!["fn a() {}", "fn b() {}", ""]
+        );
+        assert_eq!(file.line(LineNumber(3)), None);
+    }
+
+    #[test]
+    fn test_snippet_multi_line() {
+        let file = SourceFile::new("fn a() {\n    1 + 2\n}\n".to_string());
+        let snippet = file
+            .snippet(&span((0, 7), (2, 1)), ColumnEncoding::Utf8)
+            .unwrap();
+        assert_eq!(snippet.text, "{\n    1 + 2\n}");
+        assert_eq!(snippet.first_line, LineNumber(0));
+        assert_eq!(snippet.lines, vec!["fn a() {", "    1 + 2", "}"]);
+    }
+
+    #[test]
+    fn test_snippet_empty_span() {
+        let file = SourceFile::new("let x = 1;".to_string());
+        let snippet = file
+            .snippet(&span((0, 4), (0, 4)), ColumnEncoding::Utf8)
+            .unwrap();
+        assert_eq!(snippet.text, "");
+        assert_eq!(snippet.lines, vec!["let x = 1;"]);
+    }
+
+    #[test]
+    fn test_snippet_column_encodings() {
+        // 'ł' is 2 bytes in UTF-8 and 1 code unit in UTF-16, '𝄞' is 4 bytes and 2 code units.
+        let file = SourceFile::new("let ł𝄞 = x;".to_string());
+
+        let utf8 = file
+            .snippet(&span((0, 4), (0, 10)), ColumnEncoding::Utf8)
+            .unwrap();
+        assert_eq!(utf8.text, "ł𝄞");
+
+        let utf16 = file
+            .snippet(&span((0, 4), (0, 7)), ColumnEncoding::Utf16)
+            .unwrap();
+        assert_eq!(utf16.text, "ł𝄞");
+    }
+
+    #[test]
+    fn test_snippet_errors() {
+        let file = SourceFile::new("let ł𝄞 = x;".to_string());
+
+        assert!(matches!(
+            file.snippet(&span((0, 5), (0, 6)), ColumnEncoding::Utf8),
+            Err(SourceError::ColumnOutOfRange { .. })
+        ));
+        assert!(matches!(
+            file.snippet(&span((0, 6), (0, 6)), ColumnEncoding::Utf16),
+            Err(SourceError::ColumnOutOfRange { .. })
+        ));
+        assert!(matches!(
+            file.snippet(&span((0, 0), (0, 100)), ColumnEncoding::Utf8),
+            Err(SourceError::ColumnOutOfRange { .. })
+        ));
+        assert!(matches!(
+            file.snippet(&span((1, 0), (1, 0)), ColumnEncoding::Utf8),
+            Err(SourceError::LineOutOfRange(LineNumber(1)))
+        ));
+        assert!(matches!(
+            file.snippet(&span((0, 4), (0, 0)), ColumnEncoding::Utf8),
+            Err(SourceError::InvalidSpan(_))
+        ));
+    }
+
+    #[test]
+    fn test_scarb_cache_relocation() {
+        let provider = ScarbCacheSourceProvider::new("/local/cache/scarb".into());
+        assert_eq!(
+            provider.relocate("/home/ci/.cache/scarb/registry/std/v2.11.0/core/src/lib.cairo"),
+            Some("/local/cache/scarb/registry/std/v2.11.0/core/src/lib.cairo".into())
+        );
+        assert_eq!(provider.relocate("/project/src/lib.cairo"), None);
+        assert_eq!(provider.relocate("/project/registry/lib.cairo"), None);
+        assert_eq!(
+            provider
+                .relocate("/home/ci/.cache/scarb/git/checkouts/alexandria-1a2b/3c4d/src/lib.cairo"),
+            Some("/local/cache/scarb/git/checkouts/alexandria-1a2b/3c4d/src/lib.cairo".into())
+        );
+    }
+
+    #[test]
+    fn test_scarb_cache_relative_path_in() {
+        let cache_dir = Utf8Path::new("/opt/scarb-cache/");
+        assert_eq!(
+            scarb_cache_relative_path_in("/opt/scarb-cache/registry/std/src/lib.cairo", cache_dir),
+            Some("registry/std/src/lib.cairo".to_string())
+        );
+        assert_eq!(
+            scarb_cache_relative_path_in(
+                r"\opt\scarb-cache\git\checkouts\dep-1a2b\3c4d\src\lib.cairo",
+                cache_dir
+            ),
+            Some("git/checkouts/dep-1a2b/3c4d/src/lib.cairo".to_string())
+        );
+        assert_eq!(
+            scarb_cache_relative_path_in("/opt/scarb-cache/git/db/dep-1a2b", cache_dir),
+            None
+        );
+        assert_eq!(
+            scarb_cache_relative_path_in(
+                "/opt/scarb-cache-2/registry/std/src/lib.cairo",
+                cache_dir
+            ),
+            None
+        );
+        assert_eq!(
+            scarb_cache_relative_path("/opt/scarb-cache/registry/std/src/lib.cairo"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_scarb_cache_relative_path() {
+        assert_eq!(
+            scarb_cache_relative_path(
+                "/Users/ci/Library/Caches/com.swmansion.scarb/registry/std/src/lib.cairo"
+            ),
+            Some("registry/std/src/lib.cairo".to_string())
+        );
+        assert_eq!(
+            scarb_cache_relative_path(
+                r"C:\Users\ci\AppData\Local\swmansion\scarb\cache\git\checkouts\dep-1a2b\3c4d\src\lib.cairo"
+            ),
+            Some("git/checkouts/dep-1a2b/3c4d/src/lib.cairo".to_string())
+        );
+        assert_eq!(
+            scarb_cache_relative_path(
+                "/project/registry/x/.cache/scarb/registry/std/src/lib.cairo"
+            ),
+            Some("registry/std/src/lib.cairo".to_string())
+        );
+        assert_eq!(
+            scarb_cache_relative_path("/home/user/my_project/cache/registry/src/lib.cairo"),
+            None
+        );
+        assert_eq!(
+            scarb_cache_relative_path(
+                "/home/user/.cache/git/checkouts/dep-1a2b/3c4d/src/lib.cairo"
+            ),
+            None
+        );
+        assert_eq!(
+            scarb_cache_relative_path("/home/user/my-scarb/registry/src/lib.cairo"),
+            None
+        );
+    }
+}
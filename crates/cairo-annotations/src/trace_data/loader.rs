@@ -0,0 +1,455 @@
+use crate::felt_deserialize::deserialize as felt_deserialize;
+use crate::trace_data::{
+    CallTraceV1, CallTraceVersionError, CallType, EntryPointType, SyscallSelector,
+    VersionedCallTrace,
+};
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::Deserialize;
+use serde_ignored::Path;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::{fmt, fs, io};
+use strum::VariantArray;
+use strum_macros::{Display, VariantArray};
+use thiserror::Error;
+
+/// How issues found while loading a trace are handled.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum LoadMode {
+    /// Issues are returned as errors.
+    #[default]
+    Strict,
+    /// Invalid values are replaced with defaults and unknown nested calls are skipped.
+    /// Issues are reported as warnings.
+    Lenient,
+}
+
+/// Optional field of a trace, added in a later `snforge` version.
+///
+/// Displayed in `snake_case`, as in the trace file.
+#[derive(Clone, Copy, Debug, Display, Eq, Hash, Ord, PartialEq, PartialOrd, VariantArray)]
+#[strum(serialize_all = "snake_case")]
+pub enum OptionalField {
+    /// [`ExecutionResources::syscall_counter`](crate::trace_data::ExecutionResources::syscall_counter).
+    SyscallCounter,
+    /// [`CallEntryPoint::calldata_len`](crate::trace_data::CallEntryPoint::calldata_len).
+    CalldataLen,
+    /// [`CallEntryPoint::events_summary`](crate::trace_data::CallEntryPoint::events_summary).
+    EventsSummary,
+    /// [`CallEntryPoint::signature_len`](crate::trace_data::CallEntryPoint::signature_len).
+    SignatureLen,
+}
+
+/// Version of `snforge`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct SnforgeVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+/// Range of `snforge` versions that could have produced a trace.
+///
+/// Optional fields are serialized even if they are empty, so a present field means the trace was produced
+/// by a version supporting it and a missing field means it was produced by an older version.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SnforgeVersionRange {
+    /// Lowest possible version, inclusive.
+    pub min: Option<SnforgeVersion>,
+    /// Highest possible version, exclusive.
+    pub max: Option<SnforgeVersion>,
+}
+
+/// A trace loaded with [`load_call_trace`] or [`parse_call_trace`].
+#[derive(Debug, Clone)]
+pub struct LoadedCallTrace {
+    pub call_trace: VersionedCallTrace,
+    /// Optional fields present in any call of the trace.
+    pub optional_fields: BTreeSet<OptionalField>,
+    /// Versions of `snforge` that could have produced the trace, inferred from `optional_fields`.
+    pub snforge_versions: SnforgeVersionRange,
+    /// Issues which did not prevent loading the trace.
+    pub warnings: Vec<TraceIssue>,
+}
+
+/// A problem with a value in a trace.
+#[derive(Clone, Debug, Eq, PartialEq, Error)]
+#[error("{kind} at {path}")]
+pub struct TraceIssue {
+    /// Path of the value in the trace, e.g. `nested_calls[0].EntryPointCall.entry_point.class_hash`.
+    pub path: String,
+    pub kind: TraceIssueKind,
+}
+
+/// Kind of a [`TraceIssue`].
+#[derive(Clone, Debug, Eq, PartialEq, Error)]
+pub enum TraceIssueKind {
+    /// Issue indicating that the field is not known to this version of the crate.
+    /// Unknown fields are ignored, so this issue is always reported as a warning.
+    #[error("Unknown field")]
+    UnknownField,
+
+    /// Issue indicating that the variant of an enum is not known to this version of the crate.
+    /// Unknown syscall selectors are deserialized as [`SyscallSelector::Unknown`],
+    /// so for them this issue is always reported as a warning.
+    #[error("Unknown variant {0}")]
+    UnknownVariant(String),
+
+    /// Issue indicating that the value is not a valid felt.
+    #[error("Invalid felt {0}")]
+    InvalidFelt(String),
+}
+
+/// Enum representing the possible errors that can occur when loading a trace.
+#[derive(Debug, Error)]
+pub enum TraceLoadError {
+    /// Error indicating that the trace file could not be read.
+    #[error("Failed to read trace file {path}: {source}")]
+    Io {
+        path: Utf8PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    /// Error indicating that the trace is not valid JSON or misses required fields.
+    #[error("Deserialization error: {0}")]
+    DeserializationError(#[from] serde_json::Error),
+
+    /// Error indicating that the version of the trace is invalid or not supported.
+    #[error(transparent)]
+    VersionError(#[from] CallTraceVersionError),
+
+    /// Error indicating that the trace has an issue which is tolerated only in [`LoadMode::Lenient`].
+    #[error("Invalid trace: {0}")]
+    InvalidTrace(TraceIssue),
+}
+
+impl OptionalField {
+    #[must_use]
+    pub fn all() -> &'static [Self] {
+        Self::VARIANTS
+    }
+
+    /// The first `snforge` version producing the field.
+    #[must_use]
+    pub fn introduced_in(self) -> SnforgeVersion {
+        match self {
+            OptionalField::SyscallCounter => SnforgeVersion::new(0, 46, 0),
+            OptionalField::CalldataLen => SnforgeVersion::new(0, 48, 0),
+            OptionalField::EventsSummary | OptionalField::SignatureLen => {
+                SnforgeVersion::new(0, 49, 0)
+            }
+        }
+    }
+
+    /// Field of a call containing the field.
+    fn parent(self) -> &'static str {
+        match self {
+            OptionalField::SyscallCounter => "used_execution_resources",
+            OptionalField::CalldataLen
+            | OptionalField::EventsSummary
+            | OptionalField::SignatureLen => "entry_point",
+        }
+    }
+}
+
+impl SnforgeVersion {
+    #[must_use]
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl fmt::Display for SnforgeVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl SnforgeVersionRange {
+    /// Infers the range from the optional fields present in a trace.
+    ///
+    /// Missing fields introduced before the newest present field are ignored.
+    #[must_use]
+    pub fn from_optional_fields(optional_fields: &BTreeSet<OptionalField>) -> Self {
+        let min = optional_fields
+            .iter()
+            .map(|field| field.introduced_in())
+            .max();
+        let max = OptionalField::all()
+            .iter()
+            .filter(|field| !optional_fields.contains(field))
+            .map(|field| field.introduced_in())
+            .filter(|version| min.is_none_or(|min| *version > min))
+            .min();
+        Self { min, max }
+    }
+
+    #[must_use]
+    pub fn contains(&self, version: SnforgeVersion) -> bool {
+        self.min.is_none_or(|min| version >= min) && self.max.is_none_or(|max| version < max)
+    }
+}
+
+impl fmt::Display for SnforgeVersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => write!(f, ">={min}, <{max}"),
+            (Some(min), None) => write!(f, ">={min}"),
+            (None, Some(max)) => write!(f, "<{max}"),
+            (None, None) => write!(f, "*"),
+        }
+    }
+}
+
+/// Reads and loads a trace file saved by `snforge` with `--save-trace-data`.
+/// See [`parse_call_trace`].
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or the trace cannot be loaded.
+pub fn load_call_trace(path: &Utf8Path, mode: LoadMode) -> Result<LoadedCallTrace, TraceLoadError> {
+    let content = fs::read_to_string(path).map_err(|source| TraceLoadError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    parse_call_trace(&content, mode)
+}
+
+/// Loads a trace from JSON, detecting which optional fields are present and which `snforge` versions
+/// could have produced it.
+///
+/// Invalid felts and unknown enum variants are errors in [`LoadMode::Strict`] and warnings in
+/// [`LoadMode::Lenient`]. Unknown fields and syscall selectors are always warnings.
+///
+/// # Errors
+///
+/// Returns an error if the trace is not valid JSON, misses required fields,
+/// or has an issue not tolerated in `mode`.
+pub fn parse_call_trace(json: &str, mode: LoadMode) -> Result<LoadedCallTrace, TraceLoadError> {
+    let mut value: Value = serde_json::from_str(json)?;
+    // Only V1 is supported, other versions are rejected.
+    VersionedCallTrace::take_version(&mut value)?;
+
+    let mut loader = Loader {
+        mode,
+        optional_fields: BTreeSet::new(),
+        warnings: Vec::new(),
+        removed_nodes: HashMap::new(),
+    };
+    loader.check_call(&mut value, "")?;
+
+    // Fields ignored by the deserialization are not known to this version of the crate.
+    let mut unknown_fields = Vec::new();
+    let call_trace: CallTraceV1 = serde_ignored::deserialize(&value, |path| {
+        unknown_fields.push(input_path(&path, &value, &loader.removed_nodes).0);
+    })?;
+    loader
+        .warnings
+        .extend(unknown_fields.into_iter().map(|path| TraceIssue {
+            path,
+            kind: TraceIssueKind::UnknownField,
+        }));
+
+    let snforge_versions = SnforgeVersionRange::from_optional_fields(&loader.optional_fields);
+    Ok(LoadedCallTrace {
+        call_trace: VersionedCallTrace::V1(call_trace),
+        optional_fields: loader.optional_fields,
+        snforge_versions,
+        warnings: loader.warnings,
+    })
+}
+
+struct Loader {
+    mode: LoadMode,
+    optional_fields: BTreeSet<OptionalField>,
+    warnings: Vec<TraceIssue>,
+    /// Indices of unknown nested calls removed from the input, keyed by the path of their array.
+    removed_nodes: HashMap<String, Vec<usize>>,
+}
+
+impl Loader {
+    /// Reports the issue as a warning in lenient mode, or returns it as an error in strict mode.
+    fn report(&mut self, path: String, kind: TraceIssueKind) -> Result<(), TraceLoadError> {
+        let issue = TraceIssue { path, kind };
+        match self.mode {
+            LoadMode::Strict => Err(TraceLoadError::InvalidTrace(issue)),
+            LoadMode::Lenient => {
+                self.warnings.push(issue);
+                Ok(())
+            }
+        }
+    }
+
+    /// Checks values of a `CallTraceV1` which may fail the deserialization, recursing into nested calls.
+    /// In lenient mode they are replaced, so the deserialization succeeds.
+    fn check_call(&mut self, call: &mut Value, path: &str) -> Result<(), TraceLoadError> {
+        let Some(call) = call.as_object_mut() else {
+            return Ok(());
+        };
+
+        for field in OptionalField::all() {
+            if call
+                .get(field.parent())
+                .and_then(Value::as_object)
+                .is_some_and(|parent| parent.contains_key(&field.to_string()))
+            {
+                self.optional_fields.insert(*field);
+            }
+        }
+
+        if let Some(entry_point) = call.get_mut("entry_point").and_then(Value::as_object_mut) {
+            let entry_point_path = join(path, "entry_point");
+            for (field, default) in [
+                ("class_hash", Value::Null),
+                ("entry_point_selector", Value::from("0x0")),
+                ("contract_address", Value::from("0x0")),
+            ] {
+                if let Some(value) = entry_point.get_mut(field)
+                    && !(value.is_null() && default.is_null())
+                    && felt_deserialize(&*value).is_err()
+                {
+                    self.report(
+                        join(&entry_point_path, field),
+                        TraceIssueKind::InvalidFelt(value.to_string()),
+                    )?;
+                    *value = default;
+                }
+            }
+            if let Some(value) = entry_point.get_mut("entry_point_type")
+                && EntryPointType::deserialize(&*value).is_err()
+            {
+                self.report(
+                    join(&entry_point_path, "entry_point_type"),
+                    TraceIssueKind::UnknownVariant(value.to_string()),
+                )?;
+                *value = serde_json::to_value(EntryPointType::default())?;
+            }
+            if let Some(value) = entry_point.get_mut("call_type")
+                && CallType::deserialize(&*value).is_err()
+            {
+                self.report(
+                    join(&entry_point_path, "call_type"),
+                    TraceIssueKind::UnknownVariant(value.to_string()),
+                )?;
+                *value = serde_json::to_value(CallType::default())?;
+            }
+        }
+
+        if let Some(syscall_counter) = call
+            .get("used_execution_resources")
+            .and_then(|resources| resources.get("syscall_counter"))
+            .and_then(Value::as_object)
+        {
+            let syscall_counter_path = join(path, "used_execution_resources.syscall_counter");
+            for selector in syscall_counter.keys() {
+                if SyscallSelector::from(selector.as_str()).is_unknown() {
+                    self.warnings.push(TraceIssue {
+                        path: join(&syscall_counter_path, selector),
+                        kind: TraceIssueKind::UnknownVariant(selector.clone()),
+                    });
+                }
+            }
+        }
+
+        if let Some(nested_calls) = call.get_mut("nested_calls").and_then(Value::as_array_mut) {
+            let mut unknown_nodes = Vec::new();
+            for (index, node) in nested_calls.iter_mut().enumerate() {
+                let node_path = format!("{}[{index}]", join(path, "nested_calls"));
+                match node {
+                    Value::String(variant) if variant == "DeployWithoutConstructor" => {}
+                    Value::Object(node)
+                        if node.len() == 1 && node.contains_key("EntryPointCall") =>
+                    {
+                        self.check_call(
+                            &mut node["EntryPointCall"],
+                            &join(&node_path, "EntryPointCall"),
+                        )?;
+                    }
+                    _ => {
+                        let variant = match node {
+                            Value::Object(node) => {
+                                node.keys().cloned().collect::<Vec<_>>().join(", ")
+                            }
+                            _ => node.to_string(),
+                        };
+                        self.report(node_path, TraceIssueKind::UnknownVariant(variant))?;
+                        unknown_nodes.push(index);
+                    }
+                }
+            }
+            for index in unknown_nodes.iter().rev() {
+                nested_calls.remove(*index);
+            }
+            if !unknown_nodes.is_empty() {
+                self.removed_nodes
+                    .insert(join(path, "nested_calls"), unknown_nodes);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the path of a value as it appears in the input, together with the value.
+///
+/// `value` is the input with unknown nested calls removed, so indices of nested calls are shifted back
+/// by the removed ones, and the names of enum variants, which are not part of `path`, are taken from it.
+fn input_path<'a>(
+    path: &Path<'_>,
+    value: &'a Value,
+    removed_nodes: &HashMap<String, Vec<usize>>,
+) -> (String, Option<&'a Value>) {
+    match path {
+        Path::Root => (String::new(), Some(value)),
+        Path::Seq { parent, index } => {
+            let (parent_path, parent_value) = input_path(parent, value, removed_nodes);
+            let input_index = removed_nodes.get(&parent_path).into_iter().flatten().fold(
+                *index,
+                |input_index, removed| {
+                    if *removed <= input_index {
+                        input_index + 1
+                    } else {
+                        input_index
+                    }
+                },
+            );
+            (
+                format!("{parent_path}[{input_index}]"),
+                parent_value.and_then(|parent| parent.get(index)),
+            )
+        }
+        Path::Map { parent, key } => {
+            let (parent_path, parent_value) = input_path(parent, value, removed_nodes);
+            (
+                join(&parent_path, key),
+                parent_value.and_then(|parent| parent.get(key)),
+            )
+        }
+        Path::NewtypeVariant { parent } => {
+            let (parent_path, parent_value) = input_path(parent, value, removed_nodes);
+            match parent_value
+                .and_then(Value::as_object)
+                .and_then(|variant| variant.iter().next())
+            {
+                Some((name, content)) => (join(&parent_path, name), Some(content)),
+                None => (parent_path, None),
+            }
+        }
+        Path::Some { parent } | Path::NewtypeStruct { parent } => {
+            input_path(parent, value, removed_nodes)
+        }
+    }
+}
+
+fn join(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        field.to_string()
+    } else {
+        format!("{path}.{field}")
+    }
+}
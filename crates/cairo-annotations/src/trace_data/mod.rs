@@ -0,0 +1,600 @@
+use crate::felt_deserialize::deserialize as felt_deserialize;
+use camino::Utf8PathBuf;
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema, schema_for};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+use serde_json::{Value, json};
+use starknet_types_core::felt::Felt;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{AddAssign, SubAssign};
+use strum::VariantArray;
+use strum_macros::{Display, EnumString, VariantArray};
+use thiserror::Error;
+
+pub mod loader;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ClassHash(
+    #[serde(deserialize_with = "felt_deserialize")]
+    #[schemars(with = "String")]
+    pub Felt,
+);
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ContractAddress(
+    #[serde(deserialize_with = "felt_deserialize")]
+    #[schemars(with = "String")]
+    pub Felt,
+);
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct EntryPointSelector(
+    #[serde(deserialize_with = "felt_deserialize")]
+    #[schemars(with = "String")]
+    pub Felt,
+);
+
+/// Versioned representation of `CallTrace`.
+///
+/// Always prefer using this enum when Serializing/Deserializing instead of inner ones.
+///
+/// Serialized as the inner trace with an additional `version` field, e.g. `{"version": 1, "entry_point": ...}`.
+/// Traces without the `version` field, saved before it was introduced, are deserialized as [`CallTraceV1`].
+#[derive(Debug, Clone)]
+pub enum VersionedCallTrace {
+    V1(CallTraceV1),
+}
+
+/// Enum representing the possible errors that can occur when reading the version of a serialized `CallTrace`.
+#[derive(Debug, Error)]
+pub enum CallTraceVersionError {
+    /// Error indicating that the `version` field is not a positive integer.
+    #[error("Invalid call trace version: {0}")]
+    InvalidVersion(Value),
+
+    /// Error indicating that the version is not supported by this version of the crate.
+    #[error("Unsupported call trace version: {0}")]
+    UnsupportedVersion(u64),
+}
+
+impl VersionedCallTrace {
+    /// Versions of `CallTrace` supported by this version of the crate.
+    pub const SUPPORTED_VERSIONS: &[u64] = &[1];
+
+    #[must_use]
+    pub fn version(&self) -> u64 {
+        match self {
+            VersionedCallTrace::V1(_) => 1,
+        }
+    }
+
+    /// Converts the trace to the newest supported version.
+    #[must_use]
+    pub fn into_latest(self) -> CallTraceV1 {
+        match self {
+            VersionedCallTrace::V1(call_trace) => call_trace,
+        }
+    }
+
+    /// Returns the JSON Schema of the given version of `CallTrace`, including the `version` field.
+    /// The `version` field is optional, so the schema also accepts traces saved before it was introduced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the version is not supported.
+    pub fn json_schema(version: u64) -> Result<Schema, CallTraceVersionError> {
+        let mut schema = match version {
+            1 => schema_for!(CallTraceV1),
+            _ => return Err(CallTraceVersionError::UnsupportedVersion(version)),
+        };
+        schema.insert(
+            "title".to_string(),
+            Value::from(format!("CallTraceV{version}")),
+        );
+        if let Some(properties) = schema.get_mut("properties").and_then(Value::as_object_mut) {
+            properties.insert(
+                "version".to_string(),
+                json!({
+                    "description": "Version of the trace format. Missing in traces saved before it was introduced.",
+                    "const": version,
+                }),
+            );
+        }
+        Ok(schema)
+    }
+
+    /// Removes the `version` field from a serialized trace and returns it.
+    /// Traces without it are V1.
+    pub(crate) fn take_version(value: &mut Value) -> Result<u64, CallTraceVersionError> {
+        let Some(version) = value
+            .as_object_mut()
+            .and_then(|object| object.remove("version"))
+        else {
+            return Ok(1);
+        };
+        let Some(version) = version.as_u64().filter(|&version| version > 0) else {
+            return Err(CallTraceVersionError::InvalidVersion(version));
+        };
+        if Self::SUPPORTED_VERSIONS.contains(&version) {
+            Ok(version)
+        } else {
+            Err(CallTraceVersionError::UnsupportedVersion(version))
+        }
+    }
+}
+
+impl From<CallTraceV1> for VersionedCallTrace {
+    fn from(call_trace: CallTraceV1) -> Self {
+        VersionedCallTrace::V1(call_trace)
+    }
+}
+
+impl Serialize for VersionedCallTrace {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct WithVersion<'a, T> {
+            version: u64,
+            #[serde(flatten)]
+            call_trace: &'a T,
+        }
+
+        match self {
+            VersionedCallTrace::V1(call_trace) => WithVersion {
+                version: self.version(),
+                call_trace,
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for VersionedCallTrace {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut value = Value::deserialize(deserializer)?;
+        match Self::take_version(&mut value).map_err(de::Error::custom)? {
+            1 => CallTraceV1::deserialize(value)
+                .map(VersionedCallTrace::V1)
+                .map_err(de::Error::custom),
+            version => Err(de::Error::custom(
+                CallTraceVersionError::UnsupportedVersion(version),
+            )),
+        }
+    }
+}
+
+/// Tree structure representing trace of a call.
+/// This struct should be serialized and used as an input to cairo-profiler.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CallTraceV1 {
+    pub entry_point: CallEntryPoint,
+    #[serde(rename = "used_execution_resources")]
+    pub cumulative_resources: ExecutionResources,
+    pub used_l1_resources: L1Resources,
+    pub nested_calls: Vec<CallTraceNode>,
+    pub cairo_execution_info: Option<CairoExecutionInfo>,
+}
+
+/// Struct needed for function level profiling.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CairoExecutionInfo {
+    /// Path to a file with serialized `ContractClass` or `VersionedProgram`.
+    #[schemars(with = "String")]
+    pub source_sierra_path: Utf8PathBuf,
+    pub casm_level_info: CasmLevelInfo,
+    /// `enable-gas` option from `[cairo]` section in `Scarb.toml`
+    pub enable_gas: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CasmLevelInfo {
+    pub run_with_call_header: bool,
+    pub vm_trace: Vec<TraceEntry>,
+    /// Executable program offset information
+    pub program_offset: Option<usize>,
+}
+
+/// Enum representing node of a trace of a call.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub enum CallTraceNode {
+    EntryPointCall(Box<CallTraceV1>),
+    DeployWithoutConstructor,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TraceEntry {
+    pub pc: usize,
+    pub ap: usize,
+    pub fp: usize,
+}
+
+type SyscallCounter = HashMap<SyscallSelector, SyscallUsage>;
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SyscallUsage {
+    pub call_count: usize,
+    pub linear_factor: usize,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ExecutionResources {
+    pub vm_resources: VmExecutionResources,
+    pub gas_consumed: Option<u64>,
+    /// Present for `snforge` >= `0.46.0`.
+    pub syscall_counter: Option<SyscallCounter>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq, JsonSchema)]
+pub struct VmExecutionResources {
+    pub n_steps: usize,
+    pub n_memory_holes: usize,
+    pub builtin_instance_counter: HashMap<String, usize>,
+}
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Display,
+    Deserialize,
+    Serialize,
+    Eq,
+    Hash,
+    PartialEq,
+    EnumString,
+    VariantArray,
+    JsonSchema,
+)]
+pub enum DeprecatedSyscallSelector {
+    CallContract,
+    DelegateCall,
+    DelegateL1Handler,
+    Deploy,
+    EmitEvent,
+    GetBlockHash,
+    GetBlockNumber,
+    GetBlockTimestamp,
+    GetCallerAddress,
+    GetContractAddress,
+    GetClassHashAt,
+    GetExecutionInfo,
+    GetSequencerAddress,
+    GetTxInfo,
+    GetTxSignature,
+    Keccak,
+    LibraryCall,
+    LibraryCallL1Handler,
+    ReplaceClass,
+    Secp256k1Add,
+    Secp256k1GetPointFromX,
+    Secp256k1GetXy,
+    Secp256k1Mul,
+    Secp256k1New,
+    Secp256r1Add,
+    Secp256r1GetPointFromX,
+    Secp256r1GetXy,
+    Secp256r1Mul,
+    Secp256r1New,
+    SendMessageToL1,
+    StorageRead,
+    StorageWrite,
+    Sha256ProcessBlock,
+    Sha512ProcessBlock,
+    KeccakRound,
+    MetaTxV0,
+}
+
+/// Syscall selector as recorded in the syscall counter of [`ExecutionResources`].
+///
+/// Selectors added in newer Starknet versions are deserialized as [`SyscallSelector::Unknown`],
+/// so traces produced by newer tools can still be read.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum SyscallSelector {
+    Known(DeprecatedSyscallSelector),
+    /// Selector not known to this version of the crate, with its name as found in the trace.
+    Unknown(String),
+}
+
+/// Kind of operation performed by a syscall.
+#[derive(Clone, Copy, Debug, Display, Eq, Hash, PartialEq)]
+pub enum SyscallCategory {
+    /// Calls of other contracts or classes, including deployments running constructors.
+    Call,
+    /// Reads and writes of contract storage.
+    Storage,
+    /// Reads of information about the block, transaction or other contracts.
+    ExecutionInfo,
+    /// Events and messages sent to L1.
+    Messaging,
+    /// Cryptographic primitives, e.g. hashes and elliptic curve operations.
+    Cryptography,
+    /// Replacing the class of the contract.
+    ClassManagement,
+    /// Category of a [`SyscallSelector::Unknown`] selector.
+    Unknown,
+}
+
+impl DeprecatedSyscallSelector {
+    #[must_use]
+    pub fn all() -> &'static [Self] {
+        Self::VARIANTS
+    }
+
+    #[must_use]
+    pub fn category(self) -> SyscallCategory {
+        match self {
+            Self::CallContract
+            | Self::DelegateCall
+            | Self::DelegateL1Handler
+            | Self::Deploy
+            | Self::LibraryCall
+            | Self::LibraryCallL1Handler
+            | Self::MetaTxV0 => SyscallCategory::Call,
+            Self::StorageRead | Self::StorageWrite => SyscallCategory::Storage,
+            Self::GetBlockHash
+            | Self::GetBlockNumber
+            | Self::GetBlockTimestamp
+            | Self::GetCallerAddress
+            | Self::GetContractAddress
+            | Self::GetClassHashAt
+            | Self::GetExecutionInfo
+            | Self::GetSequencerAddress
+            | Self::GetTxInfo
+            | Self::GetTxSignature => SyscallCategory::ExecutionInfo,
+            Self::EmitEvent | Self::SendMessageToL1 => SyscallCategory::Messaging,
+            Self::Keccak
+            | Self::KeccakRound
+            | Self::Secp256k1Add
+            | Self::Secp256k1GetPointFromX
+            | Self::Secp256k1GetXy
+            | Self::Secp256k1Mul
+            | Self::Secp256k1New
+            | Self::Secp256r1Add
+            | Self::Secp256r1GetPointFromX
+            | Self::Secp256r1GetXy
+            | Self::Secp256r1Mul
+            | Self::Secp256r1New
+            | Self::Sha256ProcessBlock
+            | Self::Sha512ProcessBlock => SyscallCategory::Cryptography,
+            Self::ReplaceClass => SyscallCategory::ClassManagement,
+        }
+    }
+
+    /// Whether the syscall executes another entry point, producing a nested [`CallTraceV1`].
+    #[must_use]
+    pub fn is_call(self) -> bool {
+        self.category() == SyscallCategory::Call
+    }
+
+    /// Whether the cost of the syscall grows with the length of its calldata,
+    /// recorded in [`SyscallUsage::linear_factor`].
+    #[must_use]
+    pub fn has_linear_factor(self) -> bool {
+        matches!(
+            self,
+            Self::CallContract | Self::Deploy | Self::LibraryCall | Self::MetaTxV0
+        )
+    }
+}
+
+impl SyscallSelector {
+    /// The known selector, or `None` if it is not known to this version of the crate.
+    #[must_use]
+    pub fn known(&self) -> Option<DeprecatedSyscallSelector> {
+        match self {
+            Self::Known(selector) => Some(*selector),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    #[must_use]
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Self::Unknown(_))
+    }
+
+    #[must_use]
+    pub fn category(&self) -> SyscallCategory {
+        self.known().map_or(
+            SyscallCategory::Unknown,
+            DeprecatedSyscallSelector::category,
+        )
+    }
+
+    /// See [`DeprecatedSyscallSelector::is_call`]. Unknown selectors are not calls.
+    #[must_use]
+    pub fn is_call(&self) -> bool {
+        self.known().is_some_and(DeprecatedSyscallSelector::is_call)
+    }
+
+    /// See [`DeprecatedSyscallSelector::has_linear_factor`]. Unknown selectors have no linear factor.
+    #[must_use]
+    pub fn has_linear_factor(&self) -> bool {
+        self.known()
+            .is_some_and(DeprecatedSyscallSelector::has_linear_factor)
+    }
+}
+
+impl From<DeprecatedSyscallSelector> for SyscallSelector {
+    fn from(selector: DeprecatedSyscallSelector) -> Self {
+        Self::Known(selector)
+    }
+}
+
+impl From<&str> for SyscallSelector {
+    fn from(name: &str) -> Self {
+        name.parse()
+            .map_or_else(|_| Self::Unknown(name.to_string()), Self::Known)
+    }
+}
+
+impl fmt::Display for SyscallSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Known(selector) => write!(f, "{selector}"),
+            Self::Unknown(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+impl Serialize for SyscallSelector {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for SyscallSelector {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(Self::from(name.as_str()))
+    }
+}
+
+impl JsonSchema for SyscallSelector {
+    fn schema_name() -> Cow<'static, str> {
+        "SyscallSelector".into()
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        let known_selectors: Vec<String> = DeprecatedSyscallSelector::all()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        json_schema!({
+            "description": "Name of the syscall. Names other than the known ones are accepted for forward compatibility.",
+            "type": "string",
+            "examples": known_selectors,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CallEntryPoint {
+    pub class_hash: Option<ClassHash>,
+    pub entry_point_type: EntryPointType,
+    pub entry_point_selector: EntryPointSelector,
+    pub contract_address: ContractAddress,
+    pub call_type: CallType,
+
+    /// Contract name to display instead of contract address
+    pub contract_name: Option<String>,
+    /// Function name to display instead of entry point selector
+    pub function_name: Option<String>,
+    /// Calldata length to use for syscall cost estimation
+    /// Present for `snforge` >= `0.48.0`.
+    pub calldata_len: Option<usize>,
+    /// Events information to use for l2 gas cost estimation
+    /// Present for `snforge` >= `0.49.0`
+    pub events_summary: Option<Vec<SummedUpEvent>>,
+    /// Signature length to use for l2 gas cost estimation
+    /// Present for `snforge` >= `0.49.0`
+    pub signature_len: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, Deserialize, Serialize, JsonSchema)]
+pub enum CallType {
+    #[default]
+    Call = 0,
+    Delegate = 1,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq, JsonSchema)]
+pub enum EntryPointType {
+    #[serde(rename = "CONSTRUCTOR")]
+    Constructor,
+    #[serde(rename = "EXTERNAL")]
+    #[default]
+    External,
+    #[serde(rename = "L1_HANDLER")]
+    L1Handler,
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct L1Resources {
+    pub l2_l1_message_sizes: Vec<usize>,
+}
+
+impl AddAssign<&VmExecutionResources> for VmExecutionResources {
+    fn add_assign(&mut self, other: &VmExecutionResources) {
+        self.n_steps += other.n_steps;
+        self.n_memory_holes += other.n_memory_holes;
+
+        for (key, value) in &other.builtin_instance_counter {
+            *self
+                .builtin_instance_counter
+                .entry(key.clone())
+                .or_default() += *value;
+        }
+    }
+}
+
+impl SubAssign<&VmExecutionResources> for VmExecutionResources {
+    fn sub_assign(&mut self, other: &VmExecutionResources) {
+        self.n_steps = self.n_steps.saturating_sub(other.n_steps);
+        self.n_memory_holes = self.n_memory_holes.saturating_sub(other.n_memory_holes);
+
+        for (key, value) in &other.builtin_instance_counter {
+            if let Some(self_value) = self.builtin_instance_counter.get_mut(key) {
+                *self_value = self_value.saturating_sub(*value);
+            }
+        }
+        // Remove entries where the value is 0
+        self.builtin_instance_counter.retain(|_, value| *value > 0);
+    }
+}
+
+impl AddAssign<&ExecutionResources> for ExecutionResources {
+    fn add_assign(&mut self, other: &ExecutionResources) {
+        self.vm_resources += &other.vm_resources;
+        self.gas_consumed = match (self.gas_consumed, other.gas_consumed) {
+            (Some(self_gas), Some(other_gas)) => Some(self_gas + other_gas),
+            (Some(self_gas), None) => Some(self_gas),
+            (None, Some(other_gas)) => Some(other_gas),
+            (None, None) => None,
+        };
+
+        if let Some(other_counter) = &other.syscall_counter {
+            let self_counter = self.syscall_counter.get_or_insert_with(HashMap::new);
+            for (selector, usage) in other_counter {
+                self_counter
+                    .entry(selector.clone())
+                    .and_modify(|existing| {
+                        existing.call_count += usage.call_count;
+                        existing.linear_factor += usage.linear_factor;
+                    })
+                    .or_insert_with(|| usage.clone());
+            }
+        }
+    }
+}
+
+impl SubAssign<&ExecutionResources> for ExecutionResources {
+    fn sub_assign(&mut self, other: &ExecutionResources) {
+        self.vm_resources -= &other.vm_resources;
+
+        if let Some(other_gas) = other.gas_consumed
+            && let Some(self_gas) = &mut self.gas_consumed
+        {
+            *self_gas = self_gas.saturating_sub(other_gas);
+        }
+
+        if let Some(self_counter) = &mut self.syscall_counter
+            && let Some(other_counter) = &other.syscall_counter
+        {
+            for (selector, usage) in other_counter {
+                if let Some(self_usage) = self_counter.get_mut(selector) {
+                    self_usage.call_count = self_usage.call_count.saturating_sub(usage.call_count);
+                    self_usage.linear_factor =
+                        self_usage.linear_factor.saturating_sub(usage.linear_factor);
+                }
+            }
+            // Remove entries where both values are 0
+            self_counter.retain(|_, usage| usage.call_count > 0 || usage.linear_factor > 0);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SummedUpEvent {
+    pub keys_len: usize,
+    pub data_len: usize,
+}
@@ -1,11 +1,13 @@
 use assert_fs::TempDir;
 use assert_fs::fixture::PathCopy;
+use cairo_annotations::artifacts::{ContractArtifact, load_starknet_artifacts};
 use cairo_annotations::trace_data::{CairoExecutionInfo, CasmLevelInfo, VersionedCallTrace};
 use cairo_lang_sierra::debug_info::DebugInfo;
 use cairo_lang_sierra::program::{Program, ProgramArtifact, VersionedProgram};
 use cairo_lang_sierra_to_casm::compiler::{CairoProgramDebugInfo, SierraToCasmConfig};
 use cairo_lang_sierra_to_casm::metadata::{MetadataComputationConfig, calc_metadata};
 use cairo_lang_sierra_type_size::ProgramRegistryInfo;
+use camino::Utf8PathBuf;
 use serde::de::DeserializeOwned;
 use snapbox::cmd::Command as SnapboxCommand;
 use std::fs;
@@ -18,6 +20,17 @@ pub static SCARB_TEMPLATE_TRACE_FILE: LazyLock<TraceFile> = LazyLock::new(|| {
         .first_trace_file()
 });
 
+/// Builds the contracts of a test project with Scarb and loads their artifacts.
+pub fn build_starknet_artifacts(test_project_name: &str) -> StarknetArtifacts {
+    TestProject::new(test_project_name).build_starknet_artifacts(test_project_name)
+}
+
+/// Artifacts of contracts built by Scarb, kept on disk as long as this is alive.
+pub struct StarknetArtifacts {
+    _dir: TempDir,
+    pub contracts: Vec<ContractArtifact>,
+}
+
 pub struct TraceFile {
     project_dir: PathBuf,
     cairo_execution_info: CairoExecutionInfo,
@@ -32,6 +45,10 @@ impl TraceFile {
         &self.cairo_execution_info.casm_level_info
     }
 
+    pub fn get_cairo_execution_info(&self) -> &CairoExecutionInfo {
+        &self.cairo_execution_info
+    }
+
     pub fn get_debug_info(&self) -> &DebugInfo {
         self.program.debug_info.as_ref().unwrap()
     }
@@ -89,6 +106,26 @@ impl TestProject {
             .success();
         TestProjectOutput(self)
     }
+
+    fn build_starknet_artifacts(self, package_name: &str) -> StarknetArtifacts {
+        SnapboxCommand::new("scarb")
+            .arg("build")
+            .current_dir(&self.dir)
+            .assert()
+            .success();
+
+        let manifest_path = Utf8PathBuf::from_path_buf(
+            self.dir
+                .path()
+                .join(format!("target/dev/{package_name}.starknet_artifacts.json")),
+        )
+        .unwrap();
+        let contracts = load_starknet_artifacts(&manifest_path).unwrap();
+        StarknetArtifacts {
+            _dir: self.dir,
+            contracts,
+        }
+    }
 }
 
 struct TestProjectOutput(TestProject);
@@ -0,0 +1,49 @@
+use crate::helpers::test_project::SCARB_TEMPLATE_TRACE_FILE;
+use cairo_annotations::annotations::TryFromDebugInfo;
+use cairo_annotations::annotations::coverage::VersionedCoverageAnnotations;
+use cairo_annotations::artifacts::{ArtifactKind, load_sierra_artifact};
+use camino::Utf8Path;
+
+#[test]
+fn test_load_source_sierra() {
+    let artifact = SCARB_TEMPLATE_TRACE_FILE
+        .get_cairo_execution_info()
+        .load_source_sierra()
+        .unwrap();
+
+    assert_eq!(artifact.kind, ArtifactKind::Program);
+    assert_eq!(
+        artifact.program.as_ref(),
+        Some(SCARB_TEMPLATE_TRACE_FILE.get_program())
+    );
+    assert_eq!(
+        &artifact.debug_info,
+        SCARB_TEMPLATE_TRACE_FILE.get_debug_info()
+    );
+
+    assert_eq!(
+        artifact
+            .annotations::<VersionedCoverageAnnotations>()
+            .unwrap(),
+        Some(
+            VersionedCoverageAnnotations::try_from_debug_info(
+                SCARB_TEMPLATE_TRACE_FILE.get_debug_info()
+            )
+            .unwrap()
+        )
+    );
+    let annotations = artifact.all_annotations();
+    assert!(annotations.profiler.unwrap().is_some());
+    assert!(annotations.debugger.unwrap().is_some());
+    assert!(annotations.type_names.unwrap().is_some());
+}
+
+#[test]
+fn test_load_sierra_artifact_missing_file() {
+    let error = load_sierra_artifact(Utf8Path::new("non_existent.sierra.json")).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("Failed to read artifact file non_existent.sierra.json")
+    );
+}
@@ -0,0 +1,32 @@
+use crate::helpers::test_project::build_starknet_artifacts;
+use cairo_annotations::contract_class::{CompiledContractClass, load_casm_class};
+use cairo_lang_starknet_classes::contract_class::ContractClass;
+use cairo_lang_starknet_classes::felt252_serde::sierra_from_felt252s;
+use std::fs;
+
+#[test]
+fn test_from_casm_class() {
+    let artifacts = build_starknet_artifacts("hello_starknet");
+    let contract = &artifacts.contracts[0];
+    let contract_class: ContractClass =
+        serde_json::from_str(&fs::read_to_string(&contract.sierra_path).unwrap()).unwrap();
+    let (_, _, program) = sierra_from_felt252s(&contract_class.sierra_program).unwrap();
+    let entry_points = contract.artifact.entry_points.as_ref().unwrap();
+    let casm_class = load_casm_class(contract.casm_path.as_ref().unwrap()).unwrap();
+
+    // Fails with `BytecodeMismatch` if the program is compiled differently than by Scarb.
+    let compiled =
+        CompiledContractClass::from_casm_class(&program, entry_points, &casm_class).unwrap();
+
+    assert_eq!(compiled.entry_points.len(), 4);
+    for (entry_point_type, casm_entry_point) in casm_class.entry_points_by_type.iter() {
+        let entry_point = compiled
+            .entry_point(&entry_point_type, &casm_entry_point.selector)
+            .unwrap();
+        assert_eq!(entry_point.offset, casm_entry_point.offset);
+    }
+    assert_eq!(
+        CompiledContractClass::compile(&program, entry_points).unwrap(),
+        compiled
+    );
+}
@@ -1,3 +1,5 @@
+mod artifacts;
+mod contract_class;
 mod coverage_annotations;
 mod debugger_annotations;
 mod map_pcs_to_sierra_statement_ids;
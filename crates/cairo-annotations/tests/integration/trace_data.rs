@@ -1,7 +1,12 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
 
+use cairo_annotations::trace_data::loader::{
+    LoadMode, OptionalField, SnforgeVersion, parse_call_trace,
+};
 use cairo_annotations::trace_data::{
-    DeprecatedSyscallSelector, ExecutionResources, SyscallUsage, VmExecutionResources,
+    DeprecatedSyscallSelector, ExecutionResources, SyscallCategory, SyscallSelector, SyscallUsage,
+    VersionedCallTrace, VmExecutionResources,
 };
 
 #[test]
@@ -80,7 +85,7 @@ fn test_execution_resources_add() {
     };
 
     let syscall_counter1 = HashMap::from([(
-        DeprecatedSyscallSelector::Deploy,
+        DeprecatedSyscallSelector::Deploy.into(),
         SyscallUsage {
             call_count: 2,
             linear_factor: 3,
@@ -89,14 +94,14 @@ fn test_execution_resources_add() {
 
     let syscall_counter2 = HashMap::from([
         (
-            DeprecatedSyscallSelector::Deploy,
+            DeprecatedSyscallSelector::Deploy.into(),
             SyscallUsage {
                 call_count: 1,
                 linear_factor: 2,
             },
         ),
         (
-            DeprecatedSyscallSelector::EmitEvent,
+            DeprecatedSyscallSelector::EmitEvent.into(),
             SyscallUsage {
                 call_count: 3,
                 linear_factor: 0,
@@ -124,13 +129,13 @@ fn test_execution_resources_add() {
 
     let syscall_counter = er1.syscall_counter.unwrap();
     let call_contract_usage = syscall_counter
-        .get(&DeprecatedSyscallSelector::Deploy)
+        .get(&DeprecatedSyscallSelector::Deploy.into())
         .unwrap();
     assert_eq!(call_contract_usage.call_count, 3);
     assert_eq!(call_contract_usage.linear_factor, 5);
 
     let emit_event_usage = syscall_counter
-        .get(&DeprecatedSyscallSelector::EmitEvent)
+        .get(&DeprecatedSyscallSelector::EmitEvent.into())
         .unwrap();
     assert_eq!(emit_event_usage.call_count, 3);
     assert_eq!(emit_event_usage.linear_factor, 0);
@@ -152,14 +157,14 @@ fn test_execution_resources_sub() {
 
     let syscall_counter1 = HashMap::from([
         (
-            DeprecatedSyscallSelector::Deploy,
+            DeprecatedSyscallSelector::Deploy.into(),
             SyscallUsage {
                 call_count: 5,
                 linear_factor: 7,
             },
         ),
         (
-            DeprecatedSyscallSelector::EmitEvent,
+            DeprecatedSyscallSelector::EmitEvent.into(),
             SyscallUsage {
                 call_count: 3,
                 linear_factor: 0,
@@ -169,14 +174,14 @@ fn test_execution_resources_sub() {
 
     let syscall_counter2 = HashMap::from([
         (
-            DeprecatedSyscallSelector::Deploy,
+            DeprecatedSyscallSelector::Deploy.into(),
             SyscallUsage {
                 call_count: 2,
                 linear_factor: 3,
             },
         ),
         (
-            DeprecatedSyscallSelector::EmitEvent,
+            DeprecatedSyscallSelector::EmitEvent.into(),
             SyscallUsage {
                 call_count: 3,
                 linear_factor: 0,
@@ -204,11 +209,180 @@ fn test_execution_resources_sub() {
 
     let syscall_counter = er1.syscall_counter.unwrap();
     let call_contract_usage = syscall_counter
-        .get(&DeprecatedSyscallSelector::Deploy)
+        .get(&DeprecatedSyscallSelector::Deploy.into())
         .unwrap();
     assert_eq!(call_contract_usage.call_count, 3);
     assert_eq!(call_contract_usage.linear_factor, 4);
 
     // EmitEvent should be removed as both values are 0
-    assert!(!syscall_counter.contains_key(&DeprecatedSyscallSelector::EmitEvent));
+    assert!(!syscall_counter.contains_key(&DeprecatedSyscallSelector::EmitEvent.into()));
+}
+
+#[test]
+fn test_unknown_syscall_selector() {
+    let json = r#"{
+        "vm_resources": { "n_steps": 1, "n_memory_holes": 0, "builtin_instance_counter": {} },
+        "gas_consumed": null,
+        "syscall_counter": {
+            "StorageRead": { "call_count": 2, "linear_factor": 0 },
+            "FutureSyscall": { "call_count": 1, "linear_factor": 0 }
+        }
+    }"#;
+
+    let resources: ExecutionResources = serde_json::from_str(json).unwrap();
+    let syscall_counter = resources.syscall_counter.as_ref().unwrap();
+
+    assert_eq!(
+        syscall_counter[&DeprecatedSyscallSelector::StorageRead.into()].call_count,
+        2
+    );
+    let unknown = SyscallSelector::Unknown("FutureSyscall".to_string());
+    assert_eq!(syscall_counter[&unknown].call_count, 1);
+    assert!(unknown.is_unknown());
+    assert_eq!(unknown.category(), SyscallCategory::Unknown);
+    assert_eq!(unknown.to_string(), "FutureSyscall");
+
+    let serialized = serde_json::to_value(&resources).unwrap();
+    assert_eq!(
+        serialized["syscall_counter"]["FutureSyscall"]["call_count"],
+        1
+    );
+}
+
+#[test]
+fn test_syscall_selector_metadata() {
+    assert!(DeprecatedSyscallSelector::CallContract.is_call());
+    assert!(DeprecatedSyscallSelector::Deploy.has_linear_factor());
+    assert!(!DeprecatedSyscallSelector::StorageRead.is_call());
+    assert_eq!(
+        DeprecatedSyscallSelector::StorageWrite.category(),
+        SyscallCategory::Storage
+    );
+    assert!(DeprecatedSyscallSelector::all().iter().all(|&selector| {
+        SyscallSelector::from(selector.to_string().as_str()) == SyscallSelector::Known(selector)
+    }));
+    assert!(SyscallSelector::from("LibraryCall").is_call());
+    assert!(!SyscallSelector::from("FutureSyscall").has_linear_factor());
+}
+
+fn call_trace_json(nested_calls: &serde_json::Value) -> serde_json::Value {
+    serde_json::json!({
+        "entry_point": {
+            "class_hash": null,
+            "entry_point_type": "EXTERNAL",
+            "entry_point_selector": "0x1",
+            "contract_address": "123",
+            "call_type": "Call",
+            "contract_name": null,
+            "function_name": null,
+            "calldata_len": 2
+        },
+        "used_execution_resources": {
+            "vm_resources": { "n_steps": 1, "n_memory_holes": 0, "builtin_instance_counter": {} },
+            "gas_consumed": null,
+            "syscall_counter": {}
+        },
+        "used_l1_resources": { "l2_l1_message_sizes": [] },
+        "nested_calls": nested_calls,
+        "cairo_execution_info": null
+    })
+}
+
+#[test]
+fn test_load_call_trace_detects_versions() {
+    let json = call_trace_json(&serde_json::json!(["DeployWithoutConstructor"]));
+
+    let loaded = parse_call_trace(&json.to_string(), LoadMode::Strict).unwrap();
+
+    assert_eq!(
+        loaded.optional_fields,
+        BTreeSet::from([OptionalField::SyscallCounter, OptionalField::CalldataLen])
+    );
+    assert_eq!(loaded.snforge_versions.to_string(), ">=0.48.0, <0.49.0");
+    assert!(
+        loaded
+            .snforge_versions
+            .contains(SnforgeVersion::new(0, 48, 1))
+    );
+    assert!(loaded.warnings.is_empty());
+}
+
+#[test]
+fn test_load_call_trace_lenient() {
+    let mut nested_call = call_trace_json(&serde_json::json!([]));
+    nested_call["entry_point"]["contract_address"] = "not a felt".into();
+    nested_call["entry_point"]["call_type"] = "StaticCall".into();
+    nested_call["used_execution_resources"]["syscall_counter"] =
+        serde_json::json!({ "FutureSyscall": { "call_count": 1, "linear_factor": 0 } });
+    nested_call["entry_point"]["signature_len"] = serde_json::Value::Null;
+    nested_call["entry_point"]["future_len"] = 1.into();
+    nested_call["cairo_execution_info"] = serde_json::json!({
+        "source_sierra_path": "contract.sierra.json",
+        "casm_level_info": {
+            "run_with_call_header": false,
+            "vm_trace": [{ "pc": 1, "ap": 2, "fp": 3, "future_register": 4 }],
+        },
+    });
+    let mut json = call_trace_json(&serde_json::json!([
+        { "FutureCall": {} },
+        { "EntryPointCall": nested_call },
+    ]));
+    json["new_field"] = 1.into();
+
+    let error = parse_call_trace(&json.to_string(), LoadMode::Strict).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Invalid trace: Unknown variant FutureCall at nested_calls[0]"
+    );
+
+    let loaded = parse_call_trace(&json.to_string(), LoadMode::Lenient).unwrap();
+    let VersionedCallTrace::V1(call_trace) = loaded.call_trace;
+    assert_eq!(call_trace.nested_calls.len(), 1);
+    let warnings: Vec<String> = loaded.warnings.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        warnings,
+        [
+            "Unknown variant FutureCall at nested_calls[0]",
+            "Invalid felt \"not a felt\" at nested_calls[1].EntryPointCall.entry_point.contract_address",
+            "Unknown variant \"StaticCall\" at nested_calls[1].EntryPointCall.entry_point.call_type",
+            "Unknown variant FutureSyscall at nested_calls[1].EntryPointCall.used_execution_resources.syscall_counter.FutureSyscall",
+            "Unknown field at nested_calls[1].EntryPointCall.cairo_execution_info.casm_level_info.vm_trace[0].future_register",
+            "Unknown field at nested_calls[1].EntryPointCall.entry_point.future_len",
+            "Unknown field at new_field",
+        ]
+    );
+}
+
+#[test]
+fn test_versioned_call_trace_envelope() {
+    let legacy = call_trace_json(&serde_json::json!([]));
+    let call_trace: VersionedCallTrace = serde_json::from_value(legacy).unwrap();
+    assert_eq!(call_trace.version(), 1);
+
+    let serialized = serde_json::to_value(&call_trace).unwrap();
+    assert_eq!(serialized["version"], 1);
+    let VersionedCallTrace::V1(call_trace) = serde_json::from_value(serialized).unwrap();
+    assert_eq!(call_trace.entry_point.calldata_len, Some(2));
+
+    let mut future = call_trace_json(&serde_json::json!([]));
+    future["version"] = 2.into();
+    let error = serde_json::from_value::<VersionedCallTrace>(future).unwrap_err();
+    assert_eq!(error.to_string(), "Unsupported call trace version: 2");
+
+    let mut zero = call_trace_json(&serde_json::json!([]));
+    zero["version"] = 0.into();
+    let error = serde_json::from_value::<VersionedCallTrace>(zero).unwrap_err();
+    assert_eq!(error.to_string(), "Invalid call trace version: 0");
+}
+
+#[test]
+fn test_call_trace_schema_is_up_to_date() {
+    let schema = VersionedCallTrace::json_schema(1).unwrap();
+    let expected = fs::read_to_string("schemas/call_trace_v1.json").unwrap();
+
+    assert_eq!(
+        format!("{}\n", serde_json::to_string_pretty(&schema).unwrap()),
+        expected
+    );
+    assert!(VersionedCallTrace::json_schema(2).is_err());
 }